@@ -0,0 +1,303 @@
+use crate::lexer::{self, ParseError, Token};
+use crate::parser::{self, CommandError};
+use std::collections::BTreeMap;
+
+/// How many aliases deep an alias may expand before it's taken to be
+/// expanding into itself
+const MAX_DEPTH: usize = 16;
+
+/// A piece of an alias body
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    /// `$1`..`$9`, or every argument for `$@` (0). Inside double quotes the
+    /// argument is put in as text, elsewhere as it was typed.
+    Arg { index: usize, quoted: bool },
+}
+
+/// Check a name can be used for an alias
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid alias name: {} (use letters, digits, - and _)", name));
+    }
+    if parser::command_name(name).is_some() {
+        return Err(format!("'{}' is already a command", name));
+    }
+    Ok(())
+}
+
+/// Check an alias body: every command in it has to split into words
+pub fn validate_body(body: &str) -> Result<(), String> {
+    let commands = split_body(body).map_err(|err| err.message)?;
+    // Any word will do for the arguments, only the shape is checked
+    let sample = vec![("x".to_string(), "x".to_string()); 9];
+    for command in &commands {
+        let text = render(command, &sample);
+        match lexer::tokenize(&text) {
+            Ok(words) if words.is_empty() => return Err("Empty command in alias".to_string()),
+            Ok(_) => {}
+            Err(err) => return Err(err.message),
+        }
+    }
+    Ok(())
+}
+
+/// The commands `input` stands for if it starts with an alias, with its
+/// arguments filled in. Aliases used by an alias are expanded too.
+pub fn expand(aliases: &BTreeMap<String, String>, input: &str) -> Result<Option<Vec<String>>, CommandError> {
+    let tokens = lexer::tokenize(input).map_err(CommandError::Syntax)?;
+    if !tokens.first().is_some_and(|name| aliases.contains_key(&name.to_lowercase())) {
+        return Ok(None);
+    }
+
+    let mut commands = Vec::new();
+    expand_into(aliases, input, 0, &mut commands)?;
+    Ok(Some(commands))
+}
+
+fn expand_into(
+    aliases: &BTreeMap<String, String>,
+    input: &str,
+    depth: usize,
+    commands: &mut Vec<String>,
+) -> Result<(), CommandError> {
+    // A command that doesn't split into words is left for the parser to report
+    let tokens = lexer::tokenize(input).unwrap_or_default();
+    let Some((name, args)) = tokens.split_first() else {
+        commands.push(input.to_string());
+        return Ok(());
+    };
+    let Some(body) = aliases.get(&name.to_lowercase()) else {
+        commands.push(input.to_string());
+        return Ok(());
+    };
+    if depth == MAX_DEPTH {
+        return Err(CommandError::Failed(format!("Alias '{}' expands into itself", name)));
+    }
+
+    let parts = split_body(body).map_err(|err| CommandError::Failed(format!("Alias '{}': {}", name, err.message)))?;
+    let args: Vec<(String, String)> = args.iter().map(|arg| (typed(input, arg), arg.text.clone())).collect();
+
+    let (needed, takes_all) = arity(&parts);
+    if args.len() < needed || (needed > 0 && !takes_all && args.len() > needed) {
+        return Err(CommandError::Usage(format!(
+            "Alias '{}' takes {} argument{}: {}",
+            name,
+            needed,
+            if needed == 1 { "" } else { "s" },
+            body
+        )));
+    }
+
+    let last = parts.len() - 1;
+    for (i, command) in parts.iter().enumerate() {
+        let mut text = render(command, &args);
+        // Without placeholders the arguments go at the end, so
+        // `alias mv = "move"` works like the command it stands for
+        if needed == 0 && !takes_all && i == last {
+            for (word, _) in &args {
+                text.push(' ');
+                text.push_str(word);
+            }
+        }
+        expand_into(aliases, text.trim(), depth + 1, commands)?;
+    }
+    Ok(())
+}
+
+/// A word as it was typed, quotes and all
+fn typed(input: &str, token: &Token) -> String {
+    input.chars().skip(token.start).take(token.end - token.start).collect()
+}
+
+/// The highest `$n` used, and whether `$@` is
+fn arity(commands: &[Vec<Part>]) -> (usize, bool) {
+    let indexes = commands.iter().flatten().filter_map(|part| match part {
+        Part::Arg { index, .. } => Some(*index),
+        Part::Text(_) => None,
+    });
+    indexes.fold((0, false), |(needed, all), index| (needed.max(index), all || index == 0))
+}
+
+/// Fill in the arguments of one command of an alias. Arguments are pairs of
+/// how they were typed and their text.
+fn render(command: &[Part], args: &[(String, String)]) -> String {
+    let mut text = String::new();
+    for part in command {
+        match part {
+            Part::Text(s) => text.push_str(s),
+            Part::Arg { index, quoted } => {
+                let chosen = if *index == 0 { args } else { &args[index - 1..*index] };
+                let values: Vec<String> = chosen
+                    .iter()
+                    .map(|(typed, value)| {
+                        if *quoted {
+                            value.replace('\\', "\\\\").replace('"', "\\\"")
+                        } else {
+                            typed.clone()
+                        }
+                    })
+                    .collect();
+                text.push_str(&values.join(" "));
+            }
+        }
+    }
+    text
+}
+
+/// Split an alias body into its commands at each `;` outside quotes, and
+/// find the placeholders in them. Like a shell, nothing in single quotes is
+/// a placeholder and `\$` is a plain dollar sign.
+fn split_body(body: &str) -> Result<Vec<Vec<Part>>, ParseError> {
+    let chars: Vec<char> = body.chars().collect();
+    let mut commands = Vec::new();
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut quote: Option<(char, usize)> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match (c, quote) {
+            ('\\', None | Some(('"', _))) => {
+                let next = *chars
+                    .get(i + 1)
+                    .ok_or_else(|| ParseError::new("Nothing to escape after \\", i))?;
+                // The lexer keeps unknown escapes in double quotes as typed
+                if !(next == '$' && quote.is_some()) {
+                    text.push('\\');
+                }
+                text.push(next);
+                i += 2;
+                continue;
+            }
+            ('"' | '\'', None) => quote = Some((c, i)),
+            (_, Some((open, _))) if c == open => quote = None,
+            (';', None) => {
+                parts.push(Part::Text(std::mem::take(&mut text)));
+                commands.push(std::mem::take(&mut parts));
+                i += 1;
+                continue;
+            }
+            ('$', None | Some(('"', _))) => {
+                let index = match chars.get(i + 1) {
+                    Some('@') => Some(0),
+                    Some(d) => d.to_digit(10).filter(|&d| d > 0).map(|d| d as usize),
+                    None => None,
+                };
+                if let Some(index) = index {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                    parts.push(Part::Arg {
+                        index,
+                        quoted: quote.is_some(),
+                    });
+                    i += 2;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        text.push(c);
+        i += 1;
+    }
+
+    if let Some((_, opening)) = quote {
+        return Err(ParseError::new("Unclosed quote", opening));
+    }
+    parts.push(Part::Text(text));
+    commands.push(parts);
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(name, body)| (name.to_string(), body.to_string())).collect()
+    }
+
+    fn expanded(aliases: &BTreeMap<String, String>, input: &str) -> Vec<String> {
+        expand(aliases, input).unwrap().expect("input starts with an alias")
+    }
+
+    #[test]
+    fn leaves_other_input_alone() {
+        let aliases = aliases(&[("ship", "move $1 to Done")]);
+        assert_eq!(expand(&aliases, "move 1 to Done"), Ok(None));
+        assert_eq!(expand(&aliases, ""), Ok(None));
+    }
+
+    #[test]
+    fn fills_in_numbered_arguments() {
+        let aliases = aliases(&[("ship", "move $1 to Done; tag add $1 #shipped")]);
+        assert_eq!(expanded(&aliases, "SHIP 12"), ["move 12 to Done", "tag add 12 #shipped"]);
+    }
+
+    #[test]
+    fn keeps_arguments_as_typed_unless_quoted_in_the_body() {
+        let aliases = aliases(&[("todo", "add $1 to Todo"), ("note", "desc $1 append \"$2\"")]);
+        assert_eq!(expanded(&aliases, "todo \"Buy milk\""), ["add \"Buy milk\" to Todo"]);
+        assert_eq!(expanded(&aliases, "note 4 'say \"hi\"'"), [r#"desc 4 append "say \"hi\"""#]);
+    }
+
+    #[test]
+    fn fills_in_every_argument_for_all() {
+        let aliases = aliases(&[("bug", "add $@ #bug"), ("last", "move $9 to Done")]);
+        assert_eq!(expanded(&aliases, "bug \"Crash\" !high"), ["add \"Crash\" !high #bug"]);
+        assert_eq!(expanded(&aliases, "last 1 2 3 4 5 6 7 8 9"), ["move 9 to Done"]);
+    }
+
+    #[test]
+    fn checks_the_number_of_arguments() {
+        let aliases = aliases(&[("ship", "move $1 to Done"), ("last", "move $9 to Done"), ("bug", "add $1 $@")]);
+        for input in ["ship", "ship 1 2", "last 1 2 3", "bug"] {
+            assert!(matches!(expand(&aliases, input), Err(CommandError::Usage(_))), "{}", input);
+        }
+        assert_eq!(expanded(&aliases, "bug a b"), ["add a a b"]);
+    }
+
+    #[test]
+    fn appends_arguments_without_placeholders() {
+        let aliases = aliases(&[("mv", "move")]);
+        assert_eq!(expanded(&aliases, "mv 3 to \"In Review\""), ["move 3 to \"In Review\""]);
+    }
+
+    #[test]
+    fn ignores_placeholders_in_single_quotes_and_escapes() {
+        let aliases = aliases(&[("cost", "add 'costs $1' ~\\$2")]);
+        assert_eq!(expanded(&aliases, "cost"), ["add 'costs $1' ~\\$2"]);
+    }
+
+    #[test]
+    fn expands_aliases_used_by_aliases() {
+        let aliases = aliases(&[("done", "move $1 to Done"), ("ship", "done $1; tag add $1 #shipped")]);
+        assert_eq!(expanded(&aliases, "ship 5"), ["move 5 to Done", "tag add 5 #shipped"]);
+    }
+
+    #[test]
+    fn stops_aliases_that_expand_into_themselves() {
+        let aliases = aliases(&[("a", "b"), ("b", "a"), ("me", "me")]);
+        for input in ["a", "me"] {
+            assert!(
+                matches!(expand(&aliases, input), Err(CommandError::Failed(message)) if message.contains("expands into itself")),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn validates_names_and_bodies() {
+        assert!(validate_name("ship-it_2").is_ok());
+        assert!(validate_name("two words").is_err());
+        assert!(validate_name("move").is_err());
+        assert!(validate_body("move $1 to Done; undo").is_ok());
+        assert!(validate_body("add \"$1").is_err());
+        assert!(validate_body("undo;").is_err());
+    }
+}
@@ -1,197 +1,1120 @@
-use crate::state::{KanbanState, TaskId};
+use crate::alias;
+use crate::dates;
+use crate::parser::{
+    self, AliasCommand, BoardCommand, CheckCommand, ColumnCommand, Command, CommandError, DescriptionChange, NewTask, Shift,
+    TagCommand,
+};
+use crate::lexer;
+use crate::persistence;
+use crate::state::{format_estimate, sum_estimates, KanbanState, LaneMode, RemovedTask, Task, TaskId, WipPolicy};
+use std::collections::BTreeSet;
+use std::fmt;
 
-/// Parse and execute a command
+/// Parse and execute a command typed in the command bar, showing the result
+/// in the status bar. An alias is expanded into the commands it stands for.
 pub fn execute_command(input: &str, state: &mut KanbanState) {
     let input = input.trim();
     if input.is_empty() {
         return;
     }
 
-    let parts: Vec<&str> = input.split_whitespace().collect();
-    let command = parts[0].to_lowercase();
+    let result = match alias::expand(&state.aliases, input) {
+        Ok(Some(commands)) => run_alias(input, &commands, state),
+        Ok(None) => parser::parse(input, dates::today())
+            .and_then(|command| apply(command, state))
+            .map_err(|err| err.display_with(input)),
+        Err(err) => Err(err.display_with(input)),
+    };
+    match result {
+        Ok(outcome) => state.set_status(outcome.to_string()),
+        Err(message) => state.set_status(format!("Error: {}", message)),
+    }
+}
 
-    let result = match command.as_str() {
-        "add" | "a" => cmd_add(&parts[1..], state),
-        "move" | "mv" | "m" => cmd_move(&parts[1..], state),
-        "delete" | "del" | "d" => cmd_delete(&parts[1..], state),
-        "edit" | "e" => cmd_edit(&parts[1..], state),
-        "list" | "ls" | "l" => cmd_list(&parts[1..], state),
-        "clear" | "c" => {
-            // Clear all tasks (with confirmation in future)
-            for column in &mut state.columns {
-                column.tasks.clear();
-            }
-            Ok("Cleared all tasks".to_string())
-        }
-        "help" | "h" | "?" => cmd_help(),
-        _ => Err(format!("Unknown command: {}. Type 'help' for commands.", command)),
+/// Run the commands an alias expanded to. They're all parsed first, so a
+/// typo anywhere runs nothing.
+fn run_alias(input: &str, lines: &[String], state: &mut KanbanState) -> Result<Outcome, String> {
+    // Point errors at the expanded command, the input doesn't contain it
+    let in_alias = |err: CommandError, line: &str| match err.span() {
+        Some(_) => err.display_with(line),
+        None => format!("{}\n  in: {}", err, line),
     };
 
-    match result {
-        Ok(msg) => state.set_status(msg),
-        Err(err) => state.set_status(format!("Error: {}", err)),
+    let today = dates::today();
+    let commands = lines
+        .iter()
+        .map(|line| parser::parse(line, today).map_err(|err| in_alias(err, line)))
+        .collect::<Result<Vec<_>, _>>()?;
+    apply_all(commands, input, state).map_err(|(i, err)| in_alias(err, &lines[i]))
+}
+
+/// What running a command did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub message: String,
+    /// Warnings raised along the way, like a broken WIP limit
+    pub notices: Vec<String>,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)?;
+        for notice in &self.notices {
+            write!(f, "\n⚠ {}", notice)?;
+        }
+        Ok(())
     }
 }
 
-/// Add a new task
-/// Usage: add "task title" [to column] [#tag1 #tag2]
-fn cmd_add(args: &[&str], state: &mut KanbanState) -> Result<String, String> {
-    if args.is_empty() {
-        return Err("Usage: add \"task title\" [to column] [#tags]".to_string());
+/// Run a parsed command against the board
+pub fn apply(command: Command, state: &mut KanbanState) -> Result<Outcome, CommandError> {
+    let before = records_undo(&command).then(|| state.snapshot());
+    let result = run(command, state);
+    match (before, &result) {
+        (Some(before), Ok(msg)) => state.record_undo(before, msg),
+        // A command that failed halfway leaves the board as it was
+        (Some(before), Err(_)) => state.roll_back(&before),
+        (None, _) => {}
     }
 
-    // Parse task title (look for quoted string or take first arg)
-    let (title, remaining) = parse_quoted_or_first(args)?;
+    let notices = std::mem::take(&mut state.notices);
+    Ok(Outcome {
+        message: result?,
+        notices,
+    })
+}
 
-    // Parse optional "to column"
-    let mut column_name = "todo";
-    let mut tag_start = 0;
+/// Run several commands as one change that undoes in one step, for an
+/// alias. If one fails the ones before it are rolled back, and the error
+/// comes with the index of the command that failed.
+///
+/// Commands that can't be rolled back (undo, redo, boards) make the whole
+/// list run one command at a time instead, stopping at the first error.
+fn apply_all(
+    commands: Vec<Command>,
+    label: &str,
+    state: &mut KanbanState,
+) -> Result<Outcome, (usize, CommandError)> {
+    let atomic = commands.iter().all(records_undo);
+    let before = state.snapshot();
+    let mut messages = Vec::new();
 
-    for (i, arg) in remaining.iter().enumerate() {
-        if arg.eq_ignore_ascii_case("to") && i + 1 < remaining.len() {
-            column_name = remaining[i + 1];
-            tag_start = i + 2;
-            break;
+    for (i, command) in commands.into_iter().enumerate() {
+        let result = if atomic {
+            run(command, state).map_err(CommandError::Failed)
+        } else {
+            apply(command, state).map(|outcome| outcome.to_string())
+        };
+        match result {
+            Ok(message) => messages.push(message),
+            Err(err) => {
+                if atomic {
+                    state.roll_back(&before);
+                }
+                state.notices.clear();
+                return Err((i, err));
+            }
+        }
+    }
+
+    if atomic {
+        state.record_undo(before, label);
+    }
+    Ok(Outcome {
+        message: messages.join("\n"),
+        notices: std::mem::take(&mut state.notices),
+    })
+}
+
+/// Undo/redo manage the history themselves and board commands swap the
+/// whole state out, everything else is recorded if it changed the board
+fn records_undo(command: &Command) -> bool {
+    !matches!(command, Command::Undo | Command::Redo | Command::Board(_))
+}
+
+/// Run a command, without recording it for undo
+fn run(command: Command, state: &mut KanbanState) -> Result<String, String> {
+    match command {
+        Command::Add(task) => cmd_add(task, state),
+        Command::MoveTo { task, column, index } => cmd_move(task, &column, index, state),
+        Command::Shift { task, shift } => cmd_shift(task, shift, state),
+        Command::Delete(task_id) => {
+            state.delete_task(task_id)?;
+            Ok(format!("Moved task {} to trash", task_id))
+        }
+        Command::Edit { task, title } => {
+            state.edit_task_title(task, title)?;
+            Ok(format!("Updated task {}", task))
+        }
+        Command::Describe { task, change } => cmd_describe(task, change, state),
+        Command::List(column) => cmd_list(column.as_deref(), state),
+        Command::ListDue => list_due(state),
+        Command::Priority { task, priority } => {
+            state.set_priority(task, priority)?;
+            match priority {
+                Some(priority) => Ok(format!("Set priority of task {} to {}", task, priority.label())),
+                None => Ok(format!("Cleared priority of task {}", task)),
+            }
+        }
+        Command::Due { task, due } => {
+            state.set_due(task, due)?;
+            match due {
+                Some(due) => Ok(format!("Task {} is due {}", task, due)),
+                None => Ok(format!("Cleared due date of task {}", task)),
+            }
         }
-        if arg.starts_with('#') {
-            tag_start = i;
-            break;
+        Command::Estimate { task, estimate } => {
+            state.set_estimate(task, estimate)?;
+            match estimate {
+                Some(estimate) => Ok(format!("Estimated task {} at {}", task, format_estimate(estimate))),
+                None => Ok(format!("Cleared estimate of task {}", task)),
+            }
+        }
+        Command::Recur { task, rule } => {
+            let label = rule.as_ref().map(|rule| rule.label());
+            state.set_recurrence(task, rule)?;
+            match label {
+                Some(label) => Ok(format!("Task {} repeats {}", task, label)),
+                None => Ok(format!("Task {} no longer repeats", task)),
+            }
+        }
+        Command::Lane { task, lane } => {
+            state.set_lane(task, lane.clone())?;
+            match lane {
+                Some(lane) => Ok(format!("Moved task {} to lane {}", task, lane)),
+                None => Ok(format!("Removed task {} from its lane", task)),
+            }
+        }
+        Command::Lanes(None) => Ok(format!("Swimlanes: {}", state.lane_mode.label())),
+        Command::Lanes(Some(mode)) => {
+            state.lane_mode = mode;
+            Ok(match mode {
+                LaneMode::Off => "Swimlanes turned off".to_string(),
+                mode => format!("Swimlanes by {}", mode.label()),
+            })
+        }
+        Command::Tag(command) => cmd_tag(command, state),
+        Command::Check(command) => cmd_check(command, state),
+        Command::History(task_id) => cmd_history(task_id, state),
+        Command::Block { task, by } => {
+            state.add_blocker(task, by)?;
+            Ok(format!("Task {} is blocked by {}", task, by))
+        }
+        Command::Unblock { task, by } => {
+            let removed = state.remove_blocker(task, by)?;
+            match by {
+                Some(id) => Ok(format!("Task {} is no longer blocked by {}", task, id)),
+                None => Ok(format!("Removed {} blockers from task {}", removed, task)),
+            }
+        }
+        Command::Start(task_id) => match state.start_timer(task_id)? {
+            Some(stopped) => Ok(format!("Stopped timer on task {}, started task {}", stopped, task_id)),
+            None => Ok(format!("Started timer on task {}", task_id)),
+        },
+        Command::Stop => {
+            let (task_id, spent) = state.stop_timer()?;
+            Ok(format!("Stopped timer on task {} ({})", task_id, dates::format_duration(spent)))
+        }
+        Command::Time(task_id) => cmd_time(task_id, state),
+        Command::Clear => {
+            // Tasks go to the trash, so this can be reversed with restore
+            let count = state.clear_tasks();
+            Ok(format!("Moved {} tasks to trash", count))
+        }
+        Command::ArchiveTask(task_id) => {
+            state.archive_task(task_id)?;
+            Ok(format!("Archived task {}", task_id))
+        }
+        Command::ArchiveColumn(column_name) => {
+            let count = state.archive_column(&column_name)?;
+            Ok(format!("Archived {} tasks from {}", count, column_name))
+        }
+        Command::ArchiveList => Ok(list_removed("Archive", &state.archive)),
+        Command::TrashList => Ok(list_removed("Trash", &state.trash)),
+        Command::PurgeTrash { older_than } => {
+            let count = state.purge_trash(older_than);
+            Ok(format!("Permanently deleted {} tasks", count))
+        }
+        Command::Restore(task_id) => {
+            let column_name = state.restore_task(task_id)?;
+            Ok(format!("Restored task {} to {}", task_id, column_name))
         }
+        Command::Column(command) => cmd_column(command, state),
+        Command::Board(command) => cmd_board(command, state),
+        Command::Alias(command) => cmd_alias(command, state),
+        Command::Undo => state.undo(),
+        Command::Redo => state.redo(),
+        Command::Help => cmd_help(),
     }
+}
+
+/// Add a new task, to the first column unless it names one
+fn cmd_add(task: NewTask, state: &mut KanbanState) -> Result<String, String> {
+    let first_column = state.columns.first().map(|c| c.name.clone()).unwrap_or_default();
+    let column_name = task.column.unwrap_or(first_column);
+
+    let task_id = state.add_task(task.title.clone(), &column_name, task.tags)?;
+    if task.priority.is_some() {
+        state.set_priority(task_id, task.priority)?;
+    }
+    if task.due.is_some() {
+        state.set_due(task_id, task.due)?;
+    }
+    if task.lane.is_some() {
+        state.set_lane(task_id, task.lane)?;
+    }
+    if task.estimate.is_some() {
+        state.set_estimate(task_id, task.estimate)?;
+    }
+    Ok(format!("Added task '{}' to {} [{}]", task.title, column_name, task_id))
+}
+
+/// Move a task to another column, optionally at a position
+fn cmd_move(
+    task_id: TaskId,
+    column_name: &str,
+    index: Option<usize>,
+    state: &mut KanbanState,
+) -> Result<String, String> {
+    state.move_task(task_id, column_name, index)?;
+    match (index, state.task_position(task_id)) {
+        (Some(_), Some((_, pos))) => {
+            Ok(format!("Moved task to {} at position {}", column_name, pos + 1))
+        }
+        _ => Ok(format!("Moved task to {}", column_name)),
+    }
+}
+
+/// Reorder a task within its column
+fn cmd_shift(task_id: TaskId, shift: Shift, state: &mut KanbanState) -> Result<String, String> {
+    let (col_idx, pos) = state.task_position(task_id).ok_or("Task not found")?;
+    let last = state.columns[col_idx].tasks.len() - 1;
+    let index = match shift {
+        Shift::Up => pos.saturating_sub(1),
+        Shift::Down => (pos + 1).min(last),
+        Shift::Top => 0,
+        Shift::Bottom => last,
+    };
 
-    // Parse tags
-    let tags: Vec<String> = remaining[tag_start..]
+    let column_name = state.columns[col_idx].name.clone();
+    state.move_task(task_id, &column_name, Some(index))?;
+    Ok(format!("Moved task {} to position {} in {}", task_id, index + 1, column_name))
+}
+
+/// Format the archive or trash for the status bar
+fn list_removed(name: &str, removed: &[RemovedTask]) -> String {
+    if removed.is_empty() {
+        return format!("{} is empty", name);
+    }
+
+    let lines: Vec<String> = removed
         .iter()
-        .filter(|s| s.starts_with('#'))
-        .map(|s| s.trim_start_matches('#').to_string())
+        .map(|r| {
+            format!(
+                "{}  {}  (from {}, {})",
+                r.task.id,
+                r.task.title,
+                r.column,
+                r.removed_at.with_timezone(&chrono::Local).format("%Y-%m-%d")
+            )
+        })
         .collect();
 
-    let task_id = state.add_task(title.clone(), column_name, tags)?;
-    Ok(format!("Added task '{}' to {} [{}]", title, column_name, task_id))
+    format!("{} ({}):\n{}", name, removed.len(), lines.join("\n"))
 }
 
-/// Move a task to another column
-/// Usage: move <task-id> to <column>
-fn cmd_move(args: &[&str], state: &mut KanbanState) -> Result<String, String> {
-    if args.len() < 3 {
-        return Err("Usage: move <task-id> to <column>".to_string());
+/// Set, extend or clear a task's description
+fn cmd_describe(task_id: TaskId, change: DescriptionChange, state: &mut KanbanState) -> Result<String, String> {
+    match change {
+        DescriptionChange::Set(text) => {
+            state.set_description(task_id, Some(text))?;
+            Ok(format!("Set description of task {}", task_id))
+        }
+        DescriptionChange::Append(text) => {
+            state.append_description(task_id, &text)?;
+            Ok(format!("Added to description of task {}", task_id))
+        }
+        DescriptionChange::Clear => {
+            state.set_description(task_id, None)?;
+            Ok(format!("Cleared description of task {}", task_id))
+        }
     }
+}
 
-    let task_id = parse_task_id(args[0])?;
+/// List tasks in a column or all columns
+fn cmd_list(column_name: Option<&str>, state: &mut KanbanState) -> Result<String, String> {
+    let Some(column_name) = column_name else {
+        // List all
+        let total: usize = state.columns.iter().map(|c| c.tasks.len()).sum();
+        let estimate = sum_estimates(state.columns.iter().flat_map(|c| c.tasks.iter()));
+        let mut message = format!("Total tasks: {}{}", total, estimate_note(estimate));
+        if estimate.is_some() {
+            for column in &state.columns {
+                message.push_str(&format!(
+                    "\n  {}: {} tasks{}",
+                    column.name,
+                    column.tasks.len(),
+                    estimate_note(column.total_estimate())
+                ));
+            }
+        }
+        return Ok(message);
+    };
 
-    if !args[1].eq_ignore_ascii_case("to") {
-        return Err("Expected 'to' after task ID".to_string());
+    // List specific column
+    if let Some(column) = state.find_column_by_name(column_name) {
+        Ok(format!(
+            "{}: {} tasks{}",
+            column.name,
+            column.tasks.len(),
+            estimate_note(column.total_estimate())
+        ))
+    } else {
+        Err(format!("Column '{}' not found", column_name))
     }
+}
 
-    let column_name = args[2];
-    state.move_task(task_id, column_name)?;
-    Ok(format!("Moved task to {}", column_name))
+/// ", estimate 13" when there is an estimate to show
+fn estimate_note(estimate: Option<f32>) -> String {
+    estimate
+        .map(|e| format!(", estimate {}", format_estimate(e)))
+        .unwrap_or_default()
 }
 
-/// Delete a task
-/// Usage: delete <task-id>
-fn cmd_delete(args: &[&str], state: &mut KanbanState) -> Result<String, String> {
-    if args.is_empty() {
-        return Err("Usage: delete <task-id>".to_string());
+/// List tasks that have a due date, soonest first
+fn list_due(state: &KanbanState) -> Result<String, String> {
+    let today = dates::today();
+    let mut due_tasks: Vec<_> = state
+        .columns
+        .iter()
+        .flat_map(|c| c.tasks.iter().map(move |t| (c, t)))
+        .filter_map(|(c, t)| t.due.map(|due| (due, c, t)))
+        .collect();
+
+    if due_tasks.is_empty() {
+        return Ok("No tasks with due dates".to_string());
     }
 
-    let task_id = parse_task_id(args[0])?;
-    state.delete_task(task_id)?;
-    Ok(format!("Deleted task {}", args[0]))
+    due_tasks.sort_by_key(|(due, _, _)| *due);
+
+    let lines: Vec<String> = due_tasks
+        .iter()
+        .map(|(due, column, task)| {
+            let note = if *due < today {
+                " (overdue)"
+            } else if *due == today {
+                " (today)"
+            } else {
+                ""
+            };
+            format!("{}  {}{}  [{}] {}", task.id, due, note, column.name, task.title)
+        })
+        .collect();
+
+    Ok(format!("Due dates:\n{}", lines.join("\n")))
 }
 
-/// Edit a task's title
-/// Usage: edit <task-id> "new title"
-fn cmd_edit(args: &[&str], state: &mut KanbanState) -> Result<String, String> {
-    if args.len() < 2 {
-        return Err("Usage: edit <task-id> \"new title\"".to_string());
+/// Manage tags
+fn cmd_tag(command: TagCommand, state: &mut KanbanState) -> Result<String, String> {
+    match command {
+        TagCommand::List => {
+            let tags = state.known_tags();
+            if tags.is_empty() {
+                return Ok("No tags".to_string());
+            }
+            let lines: Vec<String> = tags
+                .iter()
+                .map(|tag| {
+                    let count = state
+                        .columns
+                        .iter()
+                        .flat_map(|c| c.tasks.iter())
+                        .filter(|t| t.tags.contains(tag))
+                        .count();
+                    match state.tag_colors.get(tag) {
+                        Some([r, g, b]) => format!("#{} ({})  #{:02x}{:02x}{:02x}", tag, count, r, g, b),
+                        None => format!("#{} ({})", tag, count),
+                    }
+                })
+                .collect();
+            Ok(format!("Tags:\n{}", lines.join("\n")))
+        }
+        TagCommand::Color { tag, color: None } => {
+            state.set_tag_color(&tag, None)?;
+            Ok(format!("Reset color of #{}", tag))
+        }
+        TagCommand::Color { tag, color: Some(color) } => {
+            state.set_tag_color(&tag, Some(color))?;
+            let [r, g, b] = color;
+            Ok(format!("Set color of #{} to #{:02x}{:02x}{:02x}", tag, r, g, b))
+        }
+        TagCommand::Rename { old, new } => {
+            let count = state.rename_tag(&old, &new)?;
+            Ok(format!("Renamed #{} to #{} on {} tasks", old, new, count))
+        }
+        TagCommand::Merge { from, into } => {
+            let count = state.merge_tags(&from, &into)?;
+            Ok(format!("Merged #{} into #{} on {} tasks", from, into, count))
+        }
+        TagCommand::Add { task, tags } => {
+            let mut added = Vec::new();
+            for tag in tags {
+                if state.add_tag(task, &tag)? {
+                    added.push(format!("#{}", tag));
+                }
+            }
+            if added.is_empty() {
+                Ok(format!("Task {} already has those tags", task))
+            } else {
+                Ok(format!("Tagged task {} {}", task, added.join(" ")))
+            }
+        }
+        TagCommand::Remove { task, mut tags } => {
+            // Check every tag first, so a typo doesn't leave the others removed
+            let (_, found) = state.find_task(task).ok_or("Task not found")?;
+            if let Some(missing) = tags.iter().find(|tag| !found.tags.contains(tag)) {
+                return Err(format!("Task {} isn't tagged #{}", task, missing));
+            }
+            let mut seen = BTreeSet::new();
+            tags.retain(|tag| seen.insert(tag.clone()));
+            for tag in &tags {
+                state.remove_tag(task, tag)?;
+            }
+            let names: Vec<String> = tags.iter().map(|t| format!("#{}", t)).collect();
+            Ok(format!("Removed {} from task {}", names.join(" "), task))
+        }
     }
+}
 
-    let task_id = parse_task_id(args[0])?;
-    let (new_title, _) = parse_quoted_or_first(&args[1..])?;
+/// A way to finish the word being typed in the command bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The word as it goes into the command bar, quoted if it needs to be
+    pub text: String,
+    /// Shown next to the word, like a task's title
+    pub detail: Option<String>,
+    pub kind: CompletionKind,
+}
 
-    state.edit_task_title(task_id, new_title)?;
-    Ok(format!("Updated task {}", args[0]))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Command,
+    /// A subcommand, flag or fixed value like `to` or `high`
+    Keyword,
+    Column,
+    Task,
+    Tag,
+    Lane,
+    Board,
 }
 
-/// List tasks in a column or all columns
-/// Usage: list [column]
-fn cmd_list(args: &[&str], state: &mut KanbanState) -> Result<String, String> {
-    if args.is_empty() {
-        // List all
-        let total: usize = state.columns.iter().map(|c| c.tasks.len()).sum();
-        Ok(format!("Total tasks: {}", total))
+/// Candidates for the word at the end of the command bar
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completions {
+    /// Byte offset of that word in the input; completing replaces everything after it
+    pub start: usize,
+    pub candidates: Vec<Completion>,
+}
+
+/// What could go where the cursor is, by the grammar of the command typed so far
+enum Slot {
+    Command,
+    Keywords(&'static [&'static str]),
+    Column,
+    Task,
+    /// A task in the trash or archive
+    RemovedTask,
+    /// A tag, with or without its leading #
+    Tag { hash: bool },
+    /// A task's tag or priority, typed with its marker: `#tag`, `!high`
+    Marked(&'static str, &'static [&'static str]),
+    Lane,
+    Board,
+    Alias,
+}
+
+const PRIORITIES: &[&str] = &["low", "med", "high", "urgent"];
+const WEEKDAYS: &[&str] = &["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Complete the word at the end of `input`, knowing what each command
+/// expects in each place. Task IDs also match by title.
+pub fn completions(input: &str, state: &KanbanState) -> Completions {
+    let Some((words, start, partial)) = split_last_word(input) else {
+        return Completions::default();
+    };
+
+    let candidates = expected_slots(&words, &partial)
+        .into_iter()
+        .flat_map(|slot| slot_candidates(slot, &partial, state))
+        .collect();
+    Completions { start, candidates }
+}
+
+/// Split the input into the words before the one being typed, where that
+/// word starts, and what's been typed of it. An unclosed quote is a word
+/// being typed.
+fn split_last_word(input: &str) -> Option<(Vec<String>, usize, String)> {
+    let byte_offset = |chars: usize| input.char_indices().nth(chars).map_or(input.len(), |(i, _)| i);
+
+    let (mut words, start, partial) = match lexer::tokenize(input) {
+        Ok(tokens) => {
+            let words = tokens.iter().map(|t| t.text.clone()).collect();
+            if input.is_empty() || input.ends_with(char::is_whitespace) {
+                (words, input.len(), String::new())
+            } else {
+                let last = tokens.last()?;
+                (words, byte_offset(last.start), last.text.clone())
+            }
+        }
+        Err(err) if err.message == "Unclosed quote" => {
+            let start = byte_offset(err.position);
+            let before = lexer::tokenize(&input[..start]).ok()?;
+            let mut words: Vec<String> = before.iter().map(|t| t.text.clone()).collect();
+            words.push(String::new());
+            (words, start, input[start + 1..].to_string())
+        }
+        Err(_) => return None,
+    };
+
+    if start < input.len() {
+        words.pop();
+    }
+    Some((words, start, partial))
+}
+
+/// Which kinds of word fit after `words`
+fn expected_slots(words: &[String], partial: &str) -> Vec<Slot> {
+    use Slot::*;
+
+    let Some(command) = words.first() else {
+        return vec![Command];
+    };
+    let args: Vec<String> = words[1..].iter().map(|w| w.to_lowercase()).collect();
+    let arg = |i: usize| args.get(i).map_or("", String::as_str);
+    let index = args.len();
+
+    // A #word is a tag wherever it's typed
+    if partial.starts_with('#') {
+        return vec![Tag { hash: true }];
+    }
+
+    match (parser::command_name(command).unwrap_or(""), index) {
+        ("add", 0) => vec![],
+        ("add", _) if arg(index - 1) == "to" => vec![Column],
+        ("add", _) if partial.starts_with('!') => vec![Marked("!", PRIORITIES)],
+        ("add", _) if partial.starts_with("lane:") => vec![Lane],
+        ("add", _) => vec![Keywords(&["to"])],
+        ("move", 0) => vec![Task],
+        ("move", 1) => vec![Keywords(&["to", "up", "down", "top", "bottom"])],
+        ("move", 2) if arg(1) == "to" => vec![Column],
+        ("move", 3) if arg(1) == "to" => vec![Keywords(&["at"])],
+        ("delete" | "edit" | "history" | "start" | "time", 0) => vec![Task],
+        ("desc", 0) => vec![Task],
+        ("desc", 1) => vec![Keywords(&["--clear"])],
+        ("prio", 0) | ("due", 0) | ("est", 0) | ("recur", 0) | ("lane", 0) => vec![Task],
+        ("prio", 1) => vec![Keywords(&["low", "med", "high", "urgent", "none"])],
+        ("due", 1) => vec![Keywords(&["today", "tomorrow", "none"]), Keywords(WEEKDAYS)],
+        ("est", 1) => vec![Keywords(&["none"])],
+        ("recur", 1) => vec![Keywords(&["every", "none"])],
+        ("recur", 2) if arg(1) == "every" => vec![Keywords(&["day", "weekday", "month"]), Keywords(WEEKDAYS)],
+        ("lane", 1) => vec![Lane, Keywords(&["none"])],
+        ("lanes", 0) => vec![Keywords(&["off", "lane", "tag", "priority"])],
+        ("tag", 0) => vec![Keywords(&["list", "color", "rename", "merge", "add", "rm"])],
+        ("tag", 1) => match arg(0) {
+            "color" | "colour" | "rename" | "mv" | "merge" => vec![Tag { hash: false }],
+            "add" | "rm" | "remove" => vec![Task],
+            _ => vec![],
+        },
+        ("tag", 2) if matches!(arg(0), "color" | "colour") => vec![Keywords(&["none"])],
+        ("tag", 2) if arg(0) == "merge" => vec![Tag { hash: false }],
+        ("tag", _) if matches!(arg(0), "add" | "rm" | "remove") => vec![Tag { hash: true }],
+        ("check", 0) => vec![Keywords(&["add", "toggle", "rm"])],
+        ("check", 1) => vec![Task],
+        ("block" | "unblock", 0) => vec![Task],
+        ("block" | "unblock", 1) => vec![Keywords(&["by"])],
+        ("block" | "unblock", 2) => vec![Task],
+        ("list", 0) => vec![Column, Keywords(&["due"])],
+        ("archive", 0) => vec![Column, Keywords(&["list"]), Task],
+        ("trash", 0) => vec![Keywords(&["list", "purge"])],
+        ("trash", 1) if arg(0) == "purge" => vec![Keywords(&["--older-than"])],
+        ("restore", 0) => vec![RemovedTask],
+        ("column", 0) => {
+            vec![Keywords(&["add", "rename", "delete", "move", "sort", "done", "wip", "wip-policy"])]
+        }
+        ("column", 1) => match arg(0) {
+            "add" | "a" => vec![],
+            "wip-policy" => vec![Keywords(&["warn", "refuse"])],
+            _ => vec![Column],
+        },
+        ("column", 2) => match arg(0) {
+            "sort" => vec![Keywords(&["priority", "manual"])],
+            "done" => vec![Keywords(&["on", "off"])],
+            "wip" => vec![Keywords(&["none"])],
+            "delete" | "del" | "rm" => vec![Keywords(&["--move-tasks-to"])],
+            _ => vec![],
+        },
+        ("column", 3) if arg(2) == "--move-tasks-to" => vec![Column],
+        ("board", 0) => vec![Keywords(&["list", "new", "init", "switch", "delete"])],
+        ("alias", 0) => vec![Keywords(&["list", "rm"]), Alias],
+        ("alias", 1) if matches!(arg(0), "rm" | "remove" | "del") => vec![Alias],
+        ("alias", 1) => vec![Keywords(&["="])],
+        ("board", 1) => match arg(0) {
+            "switch" | "sw" | "open" => vec![Board, Keywords(&["."])],
+            "delete" | "del" | "rm" => vec![Board],
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+/// The candidates of one kind that start with what's been typed
+fn slot_candidates(slot: Slot, partial: &str, state: &KanbanState) -> Vec<Completion> {
+    let typed = partial.to_lowercase();
+    let matches = |word: &str| word.to_lowercase().starts_with(&typed);
+    let candidate = |text: String, kind: CompletionKind| Completion {
+        text: quote_word(&text),
+        detail: None,
+        kind,
+    };
+
+    match slot {
+        Slot::Command => parser::COMMANDS
+            .iter()
+            .map(|(name, _, summary)| (name.to_string(), summary.to_string()))
+            .chain(state.aliases.iter().map(|(name, body)| (name.clone(), body.clone())))
+            .filter(|(name, _)| matches(name))
+            .map(|(name, detail)| Completion {
+                detail: Some(detail),
+                ..candidate(name, CompletionKind::Command)
+            })
+            .collect(),
+        Slot::Keywords(words) => words
+            .iter()
+            .filter(|word| matches(word))
+            .map(|word| candidate(word.to_string(), CompletionKind::Keyword))
+            .collect(),
+        Slot::Marked(marker, words) => words
+            .iter()
+            .map(|word| format!("{}{}", marker, word))
+            .filter(|word| matches(word))
+            .map(|word| candidate(word, CompletionKind::Keyword))
+            .collect(),
+        Slot::Column => state
+            .columns
+            .iter()
+            .filter(|c| matches(&c.name))
+            .map(|c| Completion {
+                detail: Some(format!("{} tasks", c.tasks.len())),
+                ..candidate(c.name.clone(), CompletionKind::Column)
+            })
+            .collect(),
+        Slot::Task => task_candidates(state.columns.iter().flat_map(|c| c.tasks.iter()), &typed),
+        Slot::RemovedTask => {
+            task_candidates(state.trash.iter().chain(&state.archive).map(|r| &r.task), &typed)
+        }
+        Slot::Tag { hash } => {
+            let marker = if hash { "#" } else { "" };
+            state
+                .known_tags()
+                .into_iter()
+                .map(|tag| format!("{}{}", marker, tag))
+                .filter(|tag| matches(tag))
+                .map(|tag| candidate(tag, CompletionKind::Tag))
+                .collect()
+        }
+        Slot::Lane => {
+            // lane:<name> when adding, just the name for the lane command
+            let marker = if partial.starts_with("lane:") { "lane:" } else { "" };
+            let lanes: BTreeSet<&String> = state
+                .columns
+                .iter()
+                .flat_map(|c| c.tasks.iter())
+                .filter_map(|t| t.lane.as_ref())
+                .collect();
+            lanes
+                .into_iter()
+                .map(|lane| format!("{}{}", marker, lane))
+                .filter(|lane| matches(lane))
+                .map(|lane| candidate(lane, CompletionKind::Lane))
+                .collect()
+        }
+        Slot::Alias => state
+            .aliases
+            .iter()
+            .filter(|(name, _)| matches(name))
+            .map(|(name, body)| Completion {
+                detail: Some(body.clone()),
+                ..candidate(name.clone(), CompletionKind::Command)
+            })
+            .collect(),
+        Slot::Board => persistence::list_boards()
+            .into_iter()
+            .filter(|name| matches(name))
+            .map(|name| candidate(name, CompletionKind::Board))
+            .collect(),
+    }
+}
+
+/// Tasks whose ID starts with, or whose title contains, what's been typed
+fn task_candidates<'a>(tasks: impl Iterator<Item = &'a Task>, typed: &str) -> Vec<Completion> {
+    tasks
+        .filter(|task| task.id.to_string().starts_with(typed) || task.title.to_lowercase().contains(typed))
+        .map(|task| Completion {
+            text: task.id.to_string(),
+            detail: Some(task.title.clone()),
+            kind: CompletionKind::Task,
+        })
+        .collect()
+}
+
+/// Quote a word for the command bar if it wouldn't survive as one bare word
+fn quote_word(word: &str) -> String {
+    let plain = !word.is_empty() && !word.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'));
+    if plain {
+        word.to_string()
     } else {
-        // List specific column
-        let column_name = args[0];
-        if let Some(column) = state.find_column_by_name(column_name) {
-            Ok(format!("{}: {} tasks", column.name, column.tasks.len()))
-        } else {
-            Err(format!("Column '{}' not found", column_name))
+        format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Manage a task's checklist
+fn cmd_check(command: CheckCommand, state: &mut KanbanState) -> Result<String, String> {
+    match command {
+        CheckCommand::Add { task, text } => {
+            let index = state.add_checklist_item(task, text)?;
+            Ok(format!("Added checklist item {} to task {}", index + 1, task))
+        }
+        CheckCommand::Toggle { task, index } => {
+            let done = state.toggle_checklist_item(task, index)?;
+            let status = if done { "done" } else { "not done" };
+            Ok(format!("Marked item {} of task {} {}", index + 1, task, status))
+        }
+        CheckCommand::Remove { task, index } => {
+            let item = state.remove_checklist_item(task, index)?;
+            Ok(format!("Removed '{}' from task {}", item.text, task))
         }
     }
 }
 
-/// Show help message
-fn cmd_help() -> Result<String, String> {
-    Ok(r#"Commands:
-  add "title" [to column] [#tags]  - Add a task
-  move <id> to <column>            - Move a task
-  delete <id>                      - Delete a task
-  edit <id> "new title"            - Edit a task
-  list [column]                    - List tasks
-  clear                            - Clear all tasks
-  help                             - Show this help
-  
-Task IDs are the first 8 characters shown on each card.
-Press Ctrl+Shift+L to toggle overlay."#.to_string())
+/// Show the time logged on a task
+fn cmd_time(task_id: TaskId, state: &KanbanState) -> Result<String, String> {
+    let (_, task) = state.find_task(task_id).ok_or("Task not found")?;
+
+    if task.time_entries.is_empty() {
+        return Ok(format!("No time logged on task {}", task_id));
+    }
+
+    let lines: Vec<String> = task
+        .time_entries
+        .iter()
+        .map(|entry| {
+            let start = entry.start.with_timezone(&chrono::Local);
+            let end = match entry.end {
+                Some(end) => end.with_timezone(&chrono::Local).format("%H:%M").to_string(),
+                None => "running".to_string(),
+            };
+            format!(
+                "{}-{}  {}",
+                start.format("%Y-%m-%d %H:%M"),
+                end,
+                dates::format_duration(entry.duration())
+            )
+        })
+        .collect();
+
+    Ok(format!(
+        "Time on {} '{}': {}\n{}",
+        task_id,
+        task.title,
+        dates::format_duration(task.time_spent()),
+        lines.join("\n")
+    ))
 }
 
-/// Parse a quoted string or take the first argument
-fn parse_quoted_or_first<'a>(args: &'a [&'a str]) -> Result<(String, Vec<&'a str>), String> {
-    if args.is_empty() {
-        return Err("Expected argument".to_string());
+/// Show a task's activity history
+fn cmd_history(task_id: TaskId, state: &KanbanState) -> Result<String, String> {
+    let (_, task) = state.find_task(task_id).ok_or("Task not found")?;
+
+    if task.history.is_empty() {
+        return Ok(format!("No history recorded for task {}", task_id));
     }
 
-    // Check if first arg starts with quote
-    if args[0].starts_with('"') {
-        // Find closing quote
-        let mut title_parts = Vec::new();
-        let mut found_closing = false;
-        let mut remaining_start = 0;
+    let lines: Vec<String> = task
+        .history
+        .iter()
+        .map(|event| {
+            format!(
+                "{}  {}",
+                event.at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                event.kind.describe()
+            )
+        })
+        .collect();
 
-        for (i, arg) in args.iter().enumerate() {
-            if i == 0 {
-                title_parts.push(arg.trim_start_matches('"'));
+    Ok(format!("History of {} '{}':\n{}", task_id, task.title, lines.join("\n")))
+}
+
+/// Manage columns
+fn cmd_column(command: ColumnCommand, state: &mut KanbanState) -> Result<String, String> {
+    match command {
+        ColumnCommand::Add(name) => {
+            state.add_column(&name)?;
+            Ok(format!("Added column {}", name))
+        }
+        ColumnCommand::Rename { name, new_name } => {
+            state.rename_column(&name, &new_name)?;
+            Ok(format!("Renamed column {} to {}", name, new_name))
+        }
+        ColumnCommand::Delete { name, move_tasks_to } => {
+            let moved = state.delete_column(&name, move_tasks_to.as_deref())?;
+            match move_tasks_to {
+                Some(target) if moved > 0 => {
+                    Ok(format!("Deleted column {} (moved {} tasks to {})", name, moved, target))
+                }
+                _ => Ok(format!("Deleted column {}", name)),
+            }
+        }
+        ColumnCommand::Move { name, index } => {
+            let index = state.move_column(&name, index)?;
+            Ok(format!("Moved column {} to position {}", name, index + 1))
+        }
+        ColumnCommand::Sort { name, by_priority } => {
+            state.set_column_sort(&name, by_priority)?;
+            if by_priority {
+                Ok(format!("Sorting {} by priority", name))
+            } else {
+                Ok(format!("Sorting {} manually", name))
+            }
+        }
+        ColumnCommand::Done { name, done } => {
+            state.set_column_done(&name, done)?;
+            if done {
+                Ok(format!("{} now counts as done", name))
             } else {
-                title_parts.push(*arg);
+                Ok(format!("{} no longer counts as done", name))
             }
+        }
+        ColumnCommand::Wip { name, limit } => {
+            state.set_wip_limit(&name, limit)?;
+            match limit {
+                Some(limit) => Ok(format!("Set WIP limit of {} to {}", name, limit)),
+                None => Ok(format!("Removed WIP limit from {}", name)),
+            }
+        }
+        ColumnCommand::WipPolicy(policy) => {
+            state.wip_policy = policy;
+            let name = match policy {
+                WipPolicy::Warn => "warn",
+                WipPolicy::Refuse => "refuse",
+            };
+            Ok(format!("WIP policy set to {}", name))
+        }
+    }
+}
 
-            if arg.ends_with('"') {
-                *title_parts.last_mut().unwrap() = title_parts.last().unwrap().trim_end_matches('"');
-                found_closing = true;
-                remaining_start = i + 1;
-                break;
+/// Manage aliases. They're saved as soon as they change, apart from the
+/// board, so they aren't part of undo.
+fn cmd_alias(command: AliasCommand, state: &mut KanbanState) -> Result<String, String> {
+    match command {
+        AliasCommand::List => {
+            if state.aliases.is_empty() {
+                return Ok("No aliases. Define one with: alias <name> = \"<command>; <command>...\"".to_string());
             }
+            let lines: Vec<String> = state
+                .aliases
+                .iter()
+                .map(|(name, body)| format!("  {} = {}", name, body))
+                .collect();
+            Ok(format!("Aliases:\n{}", lines.join("\n")))
         }
+        AliasCommand::Show(name) => {
+            let body = state.aliases.get(&name).ok_or_else(|| format!("Alias '{}' not found", name))?;
+            Ok(format!("{} = {}", name, body))
+        }
+        AliasCommand::Define { name, body } => {
+            let replaced = state.aliases.insert(name.clone(), body).is_some();
+            persistence::save_aliases(&state.aliases)?;
+            if replaced {
+                Ok(format!("Updated alias '{}'", name))
+            } else {
+                Ok(format!("Defined alias '{}'", name))
+            }
+        }
+        AliasCommand::Remove(name) => {
+            if state.aliases.remove(&name).is_none() {
+                return Err(format!("Alias '{}' not found", name));
+            }
+            persistence::save_aliases(&state.aliases)?;
+            Ok(format!("Removed alias '{}'", name))
+        }
+    }
+}
 
-        if !found_closing {
-            return Err("Unclosed quote".to_string());
+/// Manage named boards. Each board is its own file, so switching saves the
+/// current board immediately and loads the other one in its place.
+fn cmd_board(command: BoardCommand, state: &mut KanbanState) -> Result<String, String> {
+    match command {
+        BoardCommand::List => {
+            let mut lines: Vec<String> = persistence::list_boards()
+                .into_iter()
+                .map(|name| {
+                    let marker = if is_active_board(state, &name) { "*" } else { " " };
+                    format!("{} {}", marker, name)
+                })
+                .collect();
+            if let Some(path) = persistence::find_project_board() {
+                let marker = if state.board_path.as_ref() == Some(&path) { "*" } else { " " };
+                lines.push(format!(
+                    "{} {} (project, board switch .)",
+                    marker,
+                    persistence::project_board_name(&path)
+                ));
+            }
+            Ok(format!("Boards:\n{}", lines.join("\n")))
         }
+        BoardCommand::New(name) => {
+            if is_active_board(state, &name) || persistence::board_exists(&name) {
+                return Err(format!("Board '{}' already exists", name));
+            }
+
+            let board = KanbanState {
+                board_name: name.clone(),
+                ..Default::default()
+            };
+            persistence::save_board_blocking(&board)?;
+            switch_board(state, board)?;
+            Ok(format!("Created board '{}'", name))
+        }
+        BoardCommand::Init => {
+            let path = persistence::new_project_board_path()?;
+            if path.exists() {
+                return Err(format!("A project board already exists at {}", path.display()));
+            }
+
+            let board = KanbanState {
+                board_name: persistence::project_board_name(&path),
+                board_path: Some(path.clone()),
+                ..Default::default()
+            };
+            persistence::save_board_blocking(&board)?;
+            switch_board(state, board)?;
+            Ok(format!("Created project board at {}", path.display()))
+        }
+        BoardCommand::SwitchProject => {
+            let path = persistence::find_project_board()
+                .ok_or("No .kanban/board.json found here, use 'board init' to create one")?;
+            if state.board_path.as_ref() == Some(&path) {
+                return Ok(format!("Already on board '{}'", state.board_name));
+            }
+
+            let board = persistence::load_project_board_blocking(&path)?;
+            let name = board.board_name.clone();
+            switch_board(state, board)?;
+            Ok(format!("Switched to project board '{}'", name))
+        }
+        BoardCommand::Switch(name) => {
+            if is_active_board(state, &name) {
+                return Ok(format!("Already on board '{}'", name));
+            }
+
+            let board = persistence::load_board_blocking(&name)?;
+            switch_board(state, board)?;
+            Ok(format!("Switched to board '{}'", name))
+        }
+        BoardCommand::Delete(name) => {
+            if is_active_board(state, &name) {
+                return Err("Can't delete the active board, switch to another board first".to_string());
+            }
+            if !persistence::board_exists(&name) {
+                return Err(format!("Board '{}' not found", name));
+            }
 
-        let title = title_parts.join(" ").trim_matches('"').to_string();
-        let remaining = args[remaining_start..].to_vec();
-        Ok((title, remaining))
+            persistence::delete_board(&name)?;
+            Ok(format!("Deleted board '{}'", name))
+        }
+    }
+}
+
+/// Check whether the named board (not a project board) is the open one
+fn is_active_board(state: &KanbanState, name: &str) -> bool {
+    state.board_path.is_none() && state.board_name == name
+}
+
+/// Save the current board and replace it with another one
+fn switch_board(state: &mut KanbanState, board: KanbanState) -> Result<(), String> {
+    persistence::save_board_blocking(state)?;
+    let name = board.board_name.clone();
+    let is_project = board.board_path.is_some();
+    state.switch_to(board);
+
+    // Project boards are found from the working directory on startup, only
+    // named boards need remembering
+    if is_project {
+        Ok(())
     } else {
-        // No quotes, just take first arg
-        Ok((args[0].to_string(), args[1..].to_vec()))
+        persistence::set_active_board(&name)
     }
 }
 
-/// Parse a task ID (simple number)
-fn parse_task_id(id_str: &str) -> Result<TaskId, String> {
-    id_str.parse::<u32>()
-        .map_err(|_| format!("Invalid task ID: {}", id_str))
+/// Show help message
+fn cmd_help() -> Result<String, String> {
+    Ok(r#"Commands:
+  add "title" [to column] [#tags] [!prio] [due:<date>] [lane:<name>] [~est] - Add a task
+  move <id> to <column> [at <n>]   - Move a task (optionally to position n)
+  move <id> up|down|top|bottom     - Reorder a task within its column
+  delete <id>                      - Move a task to the trash
+  archive <id> | archive <column>  - Archive a task or a whole column
+  archive list / trash list        - Show archived or deleted tasks
+  restore <id>                     - Bring a task back to the board
+  trash purge [--older-than 30d]   - Permanently delete trashed tasks
+  edit <id> "new title"            - Edit a task
+  desc <id> "text" | +"more" | --clear - Set, extend or clear a description (\n for new lines)
+  prio <id> <low|med|high|urgent|none> - Set priority
+  due <id> <tomorrow|fri|2026-11-03|+3d|none> - Set due date
+  est <id> <points|none>           - Set an estimate
+  recur <id> every day|weekday|mon,thu|month on 1 - Repeat when done
+  recur <id> none                  - Stop repeating
+  check add <id> "step"            - Add a checklist item
+  check toggle|rm <id> <n>         - Tick or remove checklist item n
+  history <id>                     - Show a task's activity history
+  start <id> / stop                - Start or stop timing a task
+  time <id>                        - Show time logged on a task
+  block <id> by <id>               - Mark a task as blocked by another
+  unblock <id> [by <id>]           - Remove one or all blockers
+  tag add|rm <id> #tag...          - Add or remove tags
+  tag color <tag> <#rrggbb|none>   - Pick a tag's color
+  tag rename <old> <new>           - Rename a tag on every task
+  tag merge <from> <into>          - Fold one tag into another
+  tag list                         - List tags
+  lane <id> <name|none>            - Put a task in a swimlane
+  lanes off|lane|tag|priority      - Group the board into swimlanes
+  list [column]                    - List tasks
+  list due                         - List tasks by due date
+  column add <name>                - Add a column
+  column rename <name> <new>       - Rename a column
+  column delete <name> [--move-tasks-to <col>]
+  column move <name> <position>    - Reorder columns (1 = leftmost)
+  column sort <name> priority|manual - Sort a column by priority
+  column wip <name> <n|none>       - Set a WIP limit
+  column done <name> [off]         - Mark a column as done (unblocks dependents)
+  column wip-policy warn|refuse    - Warn about or refuse WIP overruns
+  board new|switch|delete <name>   - Create, open or delete a board
+  board list                       - List boards
+  board init                       - Create a board in this project (.kanban/board.json)
+  board switch .                   - Open this project's board
+  alias ship = "move $1 to done; tag add $1 #shipped"
+                                   - Define a command ($1..$9 and $@ for arguments)
+  alias list / alias rm <name>     - List or remove aliases
+  clear                            - Move all tasks to the trash
+  undo / redo                      - Undo or redo the last change
+  help                             - Show this help
+  
+Quote words with spaces: "two words" (\" \\ \n \t work inside) or 'taken literally'.
+Task IDs are the first 8 characters shown on each card.
+Press Tab to complete commands, columns, tags and task IDs (by ID or title).
+Press Up/Down for earlier commands, Ctrl+R to search them.
+Press Ctrl+Z / Ctrl+Shift+Z to undo/redo.
+Press Ctrl+Shift+L to toggle overlay."#.to_string())
 }
 
 /// Find task ID by short ID prefix
@@ -219,3 +1142,62 @@ pub fn parse_task_id_with_state(id_str: &str, state: &KanbanState) -> Result<Tas
     find_task_by_short_id(state, id_str)
         .ok_or_else(|| format!("Task not found: {}", id_str))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(state: &mut KanbanState, title: &str, column: &str) {
+        let task = NewTask {
+            title: title.to_string(),
+            column: Some(column.to_string()),
+            ..Default::default()
+        };
+        apply(Command::Add(task), state).unwrap();
+    }
+
+    fn column(state: &mut KanbanState, command: ColumnCommand) {
+        apply(Command::Column(command), state).unwrap();
+    }
+
+    #[test]
+    fn failed_commands_leave_the_board_and_undo_as_they_were() {
+        let mut state = KanbanState::default();
+        add(&mut state, "One", "Todo");
+        add(&mut state, "Two", "Todo");
+        add(&mut state, "Three", "Doing");
+        add(&mut state, "Four", "Doing");
+        column(
+            &mut state,
+            ColumnCommand::Wip {
+                name: "Todo".to_string(),
+                limit: Some(3),
+            },
+        );
+        let before_policy = state.snapshot();
+        column(&mut state, ColumnCommand::WipPolicy(WipPolicy::Refuse));
+        let before = state.snapshot();
+
+        // The first task fits under the limit, the second doesn't
+        let delete = ColumnCommand::Delete {
+            name: "Doing".to_string(),
+            move_tasks_to: Some("Todo".to_string()),
+        };
+        assert!(apply(Command::Column(delete), &mut state).is_err());
+        assert_eq!(state.snapshot(), before);
+
+        // Undo steps back over the last command that worked
+        apply(Command::Undo, &mut state).unwrap();
+        assert_eq!(state.snapshot(), before_policy);
+    }
+
+    #[test]
+    fn failed_commands_keep_redo() {
+        let mut state = KanbanState::default();
+        add(&mut state, "One", "Todo");
+        apply(Command::Undo, &mut state).unwrap();
+        assert!(apply(Command::Delete(9999), &mut state).is_err());
+        assert!(apply(Command::Redo, &mut state).is_ok());
+        assert_eq!(state.columns[0].tasks.len(), 1);
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Today's date in local time
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Parse a date as typed in a command, relative to `today`.
+///
+/// Accepts `today`, `tomorrow`, weekday names (`fri`, `friday` - the next
+/// one, counting today), ISO dates (`2026-11-03`) and offsets (`+3d`, `+2w`).
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" | "tmr" => return Ok(today + Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(weekday) = parse_weekday(&input) {
+        let days_ahead = (weekday.num_days_from_monday() + 7
+            - today.weekday().num_days_from_monday())
+            % 7;
+        return Ok(today + Duration::days(days_ahead as i64));
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        let invalid = || format!("Invalid date offset: {} (use +3d or +2w)", input);
+        let (amount, per_unit) = if let Some(amount) = offset.strip_suffix('d') {
+            (amount, 1)
+        } else if let Some(amount) = offset.strip_suffix('w') {
+            (amount, 7)
+        } else {
+            return Err(invalid());
+        };
+        let amount: i64 = amount.parse().map_err(|_| invalid())?;
+        if amount < 0 {
+            return Err(invalid());
+        }
+        return amount
+            .checked_mul(per_unit)
+            .and_then(Duration::try_days)
+            .and_then(|offset| today.checked_add_signed(offset))
+            .ok_or_else(invalid);
+    }
+
+    NaiveDate::parse_from_str(&input, "%Y-%m-%d").map_err(|_| {
+        format!(
+            "Invalid date: {} (try today, tomorrow, fri, 2026-11-03 or +3d)",
+            input
+        )
+    })
+}
+
+/// Parse an age like `30d`, `2w` or `12h`
+pub fn parse_age(input: &str) -> Result<Duration, String> {
+    let input = input.trim().to_lowercase();
+    let invalid = || format!("Invalid age: {} (use 30d, 2w or 12h)", input);
+    let (amount, unit): (&str, fn(i64) -> Option<Duration>) = if let Some(amount) = input.strip_suffix('h') {
+        (amount, Duration::try_hours)
+    } else if let Some(amount) = input.strip_suffix('d') {
+        (amount, Duration::try_days)
+    } else if let Some(amount) = input.strip_suffix('w') {
+        (amount, Duration::try_weeks)
+    } else {
+        return Err(invalid());
+    };
+    amount
+        .parse()
+        .ok()
+        .filter(|&amount: &i64| amount >= 0)
+        .and_then(unit)
+        .ok_or_else(invalid)
+}
+
+/// Parse a weekday name or its three-letter abbreviation
+pub fn parse_weekday(input: &str) -> Option<Weekday> {
+    match input.to_lowercase().as_str() {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Format a duration as `1h 05m`, or `12m` under an hour
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// Format a running timer as `m:ss` or `h:mm:ss`
+pub fn format_clock(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// A Wednesday
+    fn wednesday() -> NaiveDate {
+        date(2026, 10, 14)
+    }
+
+    #[test]
+    fn parses_named_dates() {
+        assert_eq!(parse_date("today", wednesday()), Ok(wednesday()));
+        assert_eq!(parse_date("Tomorrow", wednesday()), Ok(date(2026, 10, 15)));
+        assert_eq!(parse_date("tmr", wednesday()), Ok(date(2026, 10, 15)));
+    }
+
+    #[test]
+    fn weekdays_count_today() {
+        assert_eq!(parse_date("wed", wednesday()), Ok(wednesday()));
+        assert_eq!(parse_date("fri", wednesday()), Ok(date(2026, 10, 16)));
+        assert_eq!(parse_date("tuesday", wednesday()), Ok(date(2026, 10, 20)));
+    }
+
+    #[test]
+    fn parses_iso_dates_and_offsets() {
+        assert_eq!(parse_date("2026-11-03", wednesday()), Ok(date(2026, 11, 3)));
+        assert_eq!(parse_date("+3d", wednesday()), Ok(date(2026, 10, 17)));
+        assert_eq!(parse_date("+2w", wednesday()), Ok(date(2026, 10, 28)));
+        assert_eq!(parse_date("+0d", wednesday()), Ok(wednesday()));
+    }
+
+    #[test]
+    fn rejects_bad_offsets() {
+        for input in ["+", "+d", "+3", "+3m", "+-3d", "+-1w", "+99999999999999w"] {
+            assert!(parse_date(input, wednesday()).is_err(), "{} should be rejected", input);
+        }
+        assert!(parse_date("2026-02-30", wednesday()).is_err());
+        assert!(parse_date("someday", wednesday()).is_err());
+    }
+
+    #[test]
+    fn parses_ages() {
+        assert_eq!(parse_age("30d"), Ok(Duration::days(30)));
+        assert_eq!(parse_age("12H"), Ok(Duration::hours(12)));
+        assert_eq!(parse_age("2w"), Ok(Duration::weeks(2)));
+        assert_eq!(parse_age("0d"), Ok(Duration::zero()));
+    }
+
+    #[test]
+    fn rejects_bad_ages() {
+        for input in ["", "d", "30", "30m", "-5d", "-1h", "99999999999999w"] {
+            assert!(parse_age(input).is_err(), "{:?} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::minutes(12)), "12m");
+        assert_eq!(format_duration(Duration::minutes(65)), "1h 05m");
+        assert_eq!(format_clock(Duration::seconds(75)), "1:15");
+        assert_eq!(format_clock(Duration::seconds(3725)), "1:02:05");
+    }
+}
@@ -0,0 +1,124 @@
+/// Maximum number of commands kept, in memory and on disk
+pub const MAX_HISTORY: usize = 1000;
+
+/// Commands run from the command bar, oldest first, with shell-style
+/// Up/Down browsing and reverse search over them
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    entries: Vec<String>,
+    /// Entry shown in the command bar while browsing with Up/Down
+    browsing: Option<usize>,
+    /// What was typed before browsing, shown again after the newest entry
+    draft: String,
+    search: Option<HistorySearch>,
+}
+
+/// A reverse search in progress (Ctrl+R)
+#[derive(Debug, Clone, Default)]
+pub struct HistorySearch {
+    pub query: String,
+    /// Index of the entry found, if any matches
+    pub found: Option<usize>,
+}
+
+impl CommandHistory {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self {
+            entries,
+            ..Default::default()
+        }
+    }
+
+    /// Remember a command that was run. Running the same command twice in a
+    /// row only keeps it once; returns whether it was added.
+    pub fn push(&mut self, command: &str) -> bool {
+        self.browsing = None;
+        let command = command.trim();
+        if command.is_empty() || self.entries.last().is_some_and(|last| last == command) {
+            return false;
+        }
+
+        self.entries.push(command.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        true
+    }
+
+    /// Step back to an older command. `current` is the command bar text,
+    /// kept to come back to unless it's the entry already being shown.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let index = match self.browsing {
+            Some(i) if self.entries[i] == current => i.checked_sub(1)?,
+            _ => {
+                self.draft = current.to_string();
+                self.entries.len().checked_sub(1)?
+            }
+        };
+        self.browsing = Some(index);
+        Some(&self.entries[index])
+    }
+
+    /// Step forward to a newer command, or back to what was typed before
+    /// browsing once past the newest
+    pub fn newer(&mut self) -> Option<&str> {
+        let index = self.browsing?;
+        if index + 1 < self.entries.len() {
+            self.browsing = Some(index + 1);
+            Some(&self.entries[index + 1])
+        } else {
+            self.browsing = None;
+            Some(&self.draft)
+        }
+    }
+
+    pub fn search(&self) -> Option<&HistorySearch> {
+        self.search.as_ref()
+    }
+
+    pub fn start_search(&mut self) {
+        self.browsing = None;
+        self.search = Some(HistorySearch::default());
+    }
+
+    /// Search for the newest command containing `query`, ignoring case
+    pub fn set_query(&mut self, query: String) {
+        let found = self.find_before(self.entries.len(), &query);
+        self.search = Some(HistorySearch { query, found });
+    }
+
+    /// Look further back for an older match (Ctrl+R again)
+    pub fn search_older(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let from = search.found.unwrap_or(self.entries.len());
+        if let Some(older) = self.find_before(from, &search.query) {
+            self.search = Some(HistorySearch {
+                query: search.query.clone(),
+                found: Some(older),
+            });
+        }
+    }
+
+    /// The command the search has found
+    pub fn search_match(&self) -> Option<&str> {
+        let index = self.search.as_ref()?.found?;
+        Some(&self.entries[index])
+    }
+
+    pub fn end_search(&mut self) {
+        self.search = None;
+    }
+
+    /// Newest entry before `end` containing `query`
+    fn find_before(&self, end: usize, query: &str) -> Option<usize> {
+        if query.is_empty() {
+            return None;
+        }
+        let query = query.to_lowercase();
+        self.entries[..end]
+            .iter()
+            .rposition(|entry| entry.to_lowercase().contains(&query))
+    }
+}
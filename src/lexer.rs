@@ -0,0 +1,254 @@
+use std::fmt;
+use std::ops::{Deref, Range};
+
+/// A word of a command line, with quotes removed and escapes applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    /// Character offsets of the word in the input, for pointing at it in errors
+    pub start: usize,
+    pub end: usize,
+    /// How many leading characters of `text` were typed bare, outside quotes
+    /// and without escapes. Markers like `#tag` or `--flag` only count when
+    /// typed bare, so `"#1 priority"` stays plain text.
+    bare_prefix: usize,
+}
+
+impl Token {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Where the word is in the input, in characters
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// The rest of the word if it starts with `marker` typed bare
+    pub fn strip_marker(&self, marker: &str) -> Option<&str> {
+        if marker.chars().count() <= self.bare_prefix {
+            self.text.strip_prefix(marker)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the word is a bare `--flag`
+    pub fn is_flag(&self, name: &str) -> bool {
+        self.strip_marker("--").is_some_and(|flag| flag.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Deref for Token {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// A command that couldn't be parsed, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    /// Character offset in the input the error points at
+    pub position: usize,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+/// A message with the input below it, underlined at `span` with carets
+pub fn point_at(message: &str, input: &str, span: Range<usize>) -> String {
+    format!(
+        "{}\n  {}\n  {}{}",
+        message,
+        input,
+        " ".repeat(span.start),
+        "^".repeat(span.len().max(1))
+    )
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at column {})", self.message, self.position + 1)
+    }
+}
+
+/// Split a command line into words, shell style.
+///
+/// - Whitespace separates words, except inside quotes
+/// - `"double quotes"` allow escapes: `\"`, `\\`, `\n`, `\t`
+/// - `'single quotes'` are taken literally
+/// - Outside quotes a backslash escapes the next character (`\n` and `\t`
+///   still mean newline and tab)
+/// - Quoted and bare parts next to each other make one word: `+"more text"`
+/// - `--key=value` is split into `--key` and `value`, so options can be
+///   written either way
+pub fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let mut text = String::new();
+        let mut bare_prefix = None;
+
+        while i < chars.len() && !chars[i].is_whitespace() {
+            match chars[i] {
+                quote @ ('"' | '\'') => {
+                    bare_prefix.get_or_insert(text.chars().count());
+                    let opening = i;
+                    i += 1;
+                    loop {
+                        match chars.get(i) {
+                            None => return Err(ParseError::new("Unclosed quote", opening)),
+                            Some(&c) if c == quote => break,
+                            Some('\\') if quote == '"' => {
+                                i += 1;
+                                match chars.get(i) {
+                                    Some('n') => text.push('\n'),
+                                    Some('t') => text.push('\t'),
+                                    Some(&c @ ('"' | '\\')) => text.push(c),
+                                    // Unknown escapes stay as typed, so paths like C:\dir survive
+                                    Some(&c) => {
+                                        text.push('\\');
+                                        text.push(c);
+                                    }
+                                    None => return Err(ParseError::new("Unclosed quote", opening)),
+                                }
+                            }
+                            Some(&c) => text.push(c),
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                '\\' => {
+                    bare_prefix.get_or_insert(text.chars().count());
+                    i += 1;
+                    match chars.get(i) {
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some(&c) => text.push(c),
+                        None => return Err(ParseError::new("Nothing to escape after \\", i - 1)),
+                    }
+                    i += 1;
+                }
+                c => {
+                    text.push(c);
+                    i += 1;
+                }
+            }
+        }
+
+        let token = Token {
+            bare_prefix: bare_prefix.unwrap_or(text.chars().count()),
+            text,
+            start,
+            end: i,
+        };
+        tokens.extend(split_option(token));
+    }
+
+    Ok(tokens)
+}
+
+/// Split a bare `--key=value` into `--key` and `value`
+fn split_option(token: Token) -> Vec<Token> {
+    let Some(eq) = token.strip_marker("--").and_then(|_| token.text.find('=')) else {
+        return vec![token];
+    };
+    let key_len = token.text[..eq].chars().count();
+    if key_len >= token.bare_prefix {
+        return vec![token];
+    }
+
+    let key = Token {
+        text: token.text[..eq].to_string(),
+        start: token.start,
+        end: token.start + key_len,
+        bare_prefix: key_len,
+    };
+    let value = Token {
+        text: token.text[eq + 1..].to_string(),
+        start: token.start + key_len + 1,
+        end: token.end,
+        bare_prefix: token.bare_prefix - key_len - 1,
+    };
+    vec![key, value]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str) -> Vec<String> {
+        tokenize(input).unwrap().into_iter().map(|t| t.text).collect()
+    }
+
+    #[test]
+    fn splits_on_whitespace_outside_quotes() {
+        assert_eq!(words("add \"two words\" 'it''s' a\\ b"), ["add", "two words", "its", "a b"]);
+        assert_eq!(words("  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn applies_escapes() {
+        assert_eq!(words(r#""say \"hi\"\n" 'C:\dir' C:\\dir"#), ["say \"hi\"\n", "C:\\dir", "C:\\dir"]);
+    }
+
+    #[test]
+    fn spans_count_characters_not_bytes() {
+        let tokens = tokenize("add \"héllo wörld\" #ü").unwrap();
+        assert_eq!(tokens[1].text, "héllo wörld");
+        assert_eq!(tokens[1].span(), 4..17);
+        assert_eq!(tokens[2].span(), 18..20);
+    }
+
+    #[test]
+    fn points_at_unclosed_quotes() {
+        assert_eq!(tokenize("add é \"x"), Err(ParseError::new("Unclosed quote", 6)));
+        assert_eq!(tokenize("add x\\").unwrap_err().position, 5);
+    }
+
+    #[test]
+    fn markers_only_count_when_typed_bare() {
+        let tokens = tokenize("#tag \"#1 priority\" --clear \"--clear\"").unwrap();
+        assert_eq!(tokens[0].strip_marker("#"), Some("tag"));
+        assert_eq!(tokens[1].strip_marker("#"), None);
+        assert!(tokens[2].is_flag("clear"));
+        assert!(!tokens[3].is_flag("clear"));
+    }
+
+    #[test]
+    fn splits_options_at_equals() {
+        let tokens = tokenize("--older-than=30d --x=\"a b\" \"--y=1\"").unwrap();
+        let texts: Vec<&str> = tokens.iter().map(Token::as_str).collect();
+        assert_eq!(texts, ["--older-than", "30d", "--x", "a b", "--y=1"]);
+        assert_eq!(tokens[1].span(), 13..16);
+    }
+
+    #[test]
+    fn points_at_a_span_with_carets() {
+        assert_eq!(point_at("Oops", "add x", 4..5), "Oops\n  add x\n      ^");
+    }
+}
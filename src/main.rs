@@ -1,29 +1,40 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod alias;
 mod commands;
+mod dates;
+mod history;
+mod lexer;
+mod parser;
 mod persistence;
 mod state;
 mod ui;
+mod undo;
 
 use eframe::egui;
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, hotkey::{Code, Modifiers, HotKey}};
 use state::KanbanState;
 use std::sync::{Arc, Mutex, OnceLock};
 use tokio::runtime::Runtime;
-use tray_icon::{TrayIconBuilder, menu::{Menu, MenuItem}};
+use tray_icon::{TrayIcon, TrayIconBuilder, menu::{Menu, MenuItem}};
 use tray_icon::TrayIconEvent;
 
 // Global egui context for waking up the UI from background threads
 static EGUI_CTX: OnceLock<egui::Context> = OnceLock::new();
 
+// Tray tooltip while no timer is running
+const TRAY_TOOLTIP: &str = "Kanban Overlay - Ctrl+Shift+L to toggle";
+
 fn main() -> Result<(), eframe::Error> {
     // Create tokio runtime for async operations
     let rt = Runtime::new().unwrap();
 
     // Load initial state
-    let initial_state = rt.block_on(async {
+    let mut initial_state = rt.block_on(async {
         persistence::load_state().await.unwrap_or_default()
     });
+    initial_state.command_history = history::CommandHistory::new(persistence::load_history());
+    initial_state.aliases = persistence::load_aliases();
 
     // Setup global hotkey (Ctrl+Shift+L)
     let hotkey_manager = GlobalHotKeyManager::new().unwrap();
@@ -63,9 +74,10 @@ fn main() -> Result<(), eframe::Error> {
         rgba
     };
 
-    let _tray_icon = TrayIconBuilder::new()
+    // Owned by the app so the tooltip can show the running timer
+    let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(tray_menu))
-        .with_tooltip("Kanban Overlay - Ctrl+Shift+L to toggle")
+        .with_tooltip(TRAY_TOOLTIP)
         .with_icon(tray_icon::Icon::from_rgba(icon_rgba, 32, 32).unwrap())
         .build()
         .unwrap();
@@ -77,18 +89,13 @@ fn main() -> Result<(), eframe::Error> {
     std::thread::spawn(move || {
         let receiver = TrayIconEvent::receiver();
         loop {
-            if let Ok(event) = receiver.recv() {
-                // Match on Click event with Left button
-                if let TrayIconEvent::Click { button, .. } = event {
-                    if button == tray_icon::MouseButton::Left {
-                        // Left click on tray icon toggles visibility
-                        if let Ok(mut state) = app_state_tray.lock() {
-                            state.visible = !state.visible;
-                            // Wake up the UI immediately
-                            if let Some(ctx) = EGUI_CTX.get() {
-                                ctx.request_repaint();
-                            }
-                        }
+            // Only a click with the left button toggles visibility
+            if let Ok(TrayIconEvent::Click { button: tray_icon::MouseButton::Left, .. }) = receiver.recv() {
+                if let Ok(mut state) = app_state_tray.lock() {
+                    state.visible = !state.visible;
+                    // Wake up the UI immediately
+                    if let Some(ctx) = EGUI_CTX.get() {
+                        ctx.request_repaint();
                     }
                 }
             }
@@ -138,7 +145,7 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Kanban Overlay",
         options,
-        Box::new(|_cc| Ok(Box::new(KanbanApp::new(app_state, rt)))),
+        Box::new(|_cc| Ok(Box::new(KanbanApp::new(app_state, rt, tray_icon)))),
     )
 }
 
@@ -154,16 +161,45 @@ struct KanbanApp {
     normal_size: [f32; 2],
     normal_pos: Option<egui::Pos2>,  // Track window position when visible
     last_visible_state: bool,  // Track previous visibility to detect transitions
+    tray_icon: TrayIcon,
+    tray_tooltip: String,  // Last tooltip set, to only update it on change
 }
 
 impl KanbanApp {
-    fn new(state: Arc<Mutex<AppState>>, runtime: Runtime) -> Self {
+    fn new(state: Arc<Mutex<AppState>>, runtime: Runtime, tray_icon: TrayIcon) -> Self {
         Self {
             state,
             runtime,
             normal_size: [1000.0, 700.0],
             normal_pos: None,  // Will be set on first frame
             last_visible_state: true,  // Start visible
+            tray_icon,
+            tray_tooltip: TRAY_TOOLTIP.to_string(),
+        }
+    }
+
+    /// Show the running timer in the tray tooltip
+    fn update_tray_tooltip(&mut self, ctx: &egui::Context) {
+        let tooltip = {
+            let state = self.state.lock().unwrap();
+            match state.kanban.running_timer() {
+                Some((task, started)) => {
+                    // Tooltip shows minutes, so once a minute is enough even while hidden
+                    ctx.request_repaint_after(std::time::Duration::from_secs(60));
+                    format!(
+                        "Kanban Overlay - ⏱ {} {} ({})",
+                        task.id,
+                        task.title,
+                        dates::format_duration(chrono::Utc::now() - started)
+                    )
+                }
+                None => TRAY_TOOLTIP.to_string(),
+            }
+        };
+
+        if tooltip != self.tray_tooltip {
+            let _ = self.tray_icon.set_tooltip(Some(&tooltip));
+            self.tray_tooltip = tooltip;
         }
     }
 }
@@ -234,7 +270,11 @@ impl eframe::App for KanbanApp {
                     })
                 });
 
-                if should_save {
+                // Mouse edits (drag & drop, checkboxes, the task editor) don't
+                // show up as key events, so they flag themselves instead
+                let has_unsaved_changes = std::mem::take(&mut app_state.kanban.unsaved_changes);
+
+                if should_save || has_unsaved_changes {
                     app_state.saver.save(app_state.kanban.clone());
                 }
             }
@@ -275,7 +315,10 @@ impl eframe::App for KanbanApp {
             // When hidden: DO NOT request periodic repaints
             // Background threads will call ctx.request_repaint() when needed
             // This achieves TRUE 0% CPU usage like FlowLauncher!
+            // (The one exception is a running timer, see update_tray_tooltip.)
         }
+
+        self.update_tray_tooltip(ctx);
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
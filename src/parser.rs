@@ -0,0 +1,999 @@
+use crate::alias;
+use crate::dates;
+use crate::lexer::{self, ParseError, Token};
+use crate::persistence;
+use crate::state::{LaneMode, Priority, Recurrence, TaskId, WipPolicy};
+use chrono::{Duration, NaiveDate};
+use std::fmt;
+use std::ops::Range;
+
+/// A command as typed in the command bar, parsed but not yet run.
+///
+/// Parsing never looks at the board: column names, task IDs and so on are
+/// only checked when the command is applied. Relative dates like `fri` are
+/// resolved against the day passed to [`parse`], so a parsed command always
+/// means the same day.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Add(NewTask),
+    /// Move a task to a column, optionally at a 0-based index
+    MoveTo {
+        task: TaskId,
+        column: String,
+        index: Option<usize>,
+    },
+    /// Reorder a task within its column
+    Shift { task: TaskId, shift: Shift },
+    Delete(TaskId),
+    Edit { task: TaskId, title: String },
+    Describe { task: TaskId, change: DescriptionChange },
+    /// Count tasks on the board, or in one column
+    List(Option<String>),
+    ListDue,
+    Priority { task: TaskId, priority: Option<Priority> },
+    Due { task: TaskId, due: Option<NaiveDate> },
+    Estimate { task: TaskId, estimate: Option<f32> },
+    Recur { task: TaskId, rule: Option<Recurrence> },
+    Lane { task: TaskId, lane: Option<String> },
+    /// Show the swimlane mode, or change it
+    Lanes(Option<LaneMode>),
+    Tag(TagCommand),
+    Check(CheckCommand),
+    History(TaskId),
+    Block { task: TaskId, by: TaskId },
+    /// Remove one blocker, or all of them
+    Unblock { task: TaskId, by: Option<TaskId> },
+    Start(TaskId),
+    Stop,
+    Time(TaskId),
+    Clear,
+    ArchiveTask(TaskId),
+    ArchiveColumn(String),
+    ArchiveList,
+    TrashList,
+    PurgeTrash { older_than: Option<Duration> },
+    Restore(TaskId),
+    Column(ColumnCommand),
+    Board(BoardCommand),
+    Alias(AliasCommand),
+    Undo,
+    Redo,
+    Help,
+}
+
+/// A task to add. Without a column it goes in the first one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewTask {
+    pub title: String,
+    pub column: Option<String>,
+    pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    pub due: Option<NaiveDate>,
+    pub lane: Option<String>,
+    pub estimate: Option<f32>,
+}
+
+/// Where `move <id> up|down|top|bottom` puts a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shift {
+    Up,
+    Down,
+    Top,
+    Bottom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptionChange {
+    Set(String),
+    /// Add a line to the end
+    Append(String),
+    Clear,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagCommand {
+    List,
+    Color { tag: String, color: Option<[u8; 3]> },
+    Rename { old: String, new: String },
+    Merge { from: String, into: String },
+    Add { task: TaskId, tags: Vec<String> },
+    Remove { task: TaskId, tags: Vec<String> },
+}
+
+/// Checklist changes. Items are 0-based here, 1-based when typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckCommand {
+    Add { task: TaskId, text: String },
+    Toggle { task: TaskId, index: usize },
+    Remove { task: TaskId, index: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnCommand {
+    Add(String),
+    Rename { name: String, new_name: String },
+    Delete {
+        name: String,
+        move_tasks_to: Option<String>,
+    },
+    /// Move a column to a 0-based index
+    Move { name: String, index: usize },
+    Sort { name: String, by_priority: bool },
+    Done { name: String, done: bool },
+    Wip { name: String, limit: Option<usize> },
+    WipPolicy(WipPolicy),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardCommand {
+    List,
+    New(String),
+    /// Create a board for the current project
+    Init,
+    Switch(String),
+    /// Open the current project's board (`board switch .`)
+    SwitchProject,
+    Delete(String),
+}
+
+/// Alias changes. Names are lowercase, bodies as they'll be expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasCommand {
+    List,
+    Show(String),
+    Define { name: String, body: String },
+    Remove(String),
+}
+
+/// Why a command couldn't be parsed or run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// The input couldn't be split into words, e.g. an unclosed quote
+    Syntax(ParseError),
+    /// The first word isn't a command
+    UnknownCommand { name: String, span: Range<usize> },
+    /// Arguments are missing. Holds the usage line.
+    Usage(String),
+    /// An argument is malformed or out of place
+    InvalidArgument { message: String, span: Range<usize> },
+    /// The command was understood but couldn't be carried out
+    Failed(String),
+}
+
+impl CommandError {
+    /// The part of the input the error is about, in characters
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Syntax(err) => Some(err.position..err.position + 1),
+            Self::UnknownCommand { span, .. } | Self::InvalidArgument { span, .. } => Some(span.clone()),
+            Self::Usage(_) | Self::Failed(_) => None,
+        }
+    }
+
+    /// The error, with the input and carets under the offending part when
+    /// there is one
+    pub fn display_with(&self, input: &str) -> String {
+        match self.span() {
+            Some(span) => lexer::point_at(&self.to_string(), input, span),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(err) => f.write_str(&err.message),
+            Self::UnknownCommand { name, .. } => {
+                write!(f, "Unknown command: {}. Type 'help' for commands.", name)
+            }
+            Self::Usage(message) | Self::InvalidArgument { message, .. } | Self::Failed(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
+    }
+}
+
+/// Every command: its name, its aliases and what it does
+pub const COMMANDS: &[(&str, &[&str], &str)] = &[
+    ("add", &["a"], "Add a task"),
+    ("move", &["mv", "m"], "Move or reorder a task"),
+    ("delete", &["del", "d"], "Move a task to the trash"),
+    ("edit", &["e"], "Change a task's title"),
+    ("desc", &["description"], "Set a task's description"),
+    ("list", &["ls", "l"], "Count tasks or list due dates"),
+    ("column", &["col"], "Manage columns"),
+    ("prio", &["priority", "p"], "Set a task's priority"),
+    ("due", &[], "Set a task's due date"),
+    ("est", &["estimate"], "Estimate a task"),
+    ("recur", &[], "Repeat a task when it's done"),
+    ("lane", &[], "Put a task in a swimlane"),
+    ("lanes", &[], "Group the board into swimlanes"),
+    ("tag", &["tags"], "Manage tags"),
+    ("check", &["ck"], "Edit a task's checklist"),
+    ("history", &["hist"], "Show a task's activity history"),
+    ("block", &[], "Mark a task as blocked by another"),
+    ("unblock", &[], "Remove blockers from a task"),
+    ("start", &[], "Start timing a task"),
+    ("stop", &[], "Stop the running timer"),
+    ("time", &[], "Show time logged on a task"),
+    ("clear", &["c"], "Move all tasks to the trash"),
+    ("archive", &["ar"], "Archive a task or a whole column"),
+    ("trash", &[], "List or empty the trash"),
+    ("restore", &[], "Bring a task back to the board"),
+    ("board", &["b"], "Manage boards"),
+    ("alias", &[], "Define your own commands"),
+    ("undo", &["u"], "Undo the last change"),
+    ("redo", &[], "Redo the last undone change"),
+    ("help", &["h", "?"], "Show all commands"),
+];
+
+/// The name of a command, given its name or one of its aliases
+pub fn command_name(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    COMMANDS
+        .iter()
+        .find(|(name, aliases, _)| *name == word || aliases.contains(&word.as_str()))
+        .map(|(name, _, _)| *name)
+}
+
+/// Parse a command line into a [`Command`] without running it. Relative
+/// dates count from `today`.
+pub fn parse(input: &str, today: NaiveDate) -> Result<Command, CommandError> {
+    let tokens = lexer::tokenize(input).map_err(CommandError::Syntax)?;
+    let Some((name, args)) = tokens.split_first() else {
+        return Err(usage("Type a command, or 'help' to see them all"));
+    };
+
+    let unknown = || CommandError::UnknownCommand {
+        name: name.text.clone(),
+        span: name.span(),
+    };
+
+    match command_name(name).ok_or_else(unknown)? {
+        "add" => parse_add(args, today),
+        "move" => parse_move(args),
+        "delete" => Ok(Command::Delete(task_arg(args, "Usage: delete <task-id>")?)),
+        "edit" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: edit <task-id> \"new title\""));
+            }
+            if let Some(extra) = args.get(2) {
+                return Err(unquoted(extra));
+            }
+            Ok(Command::Edit {
+                task: task_id(&args[0])?,
+                title: args[1].text.clone(),
+            })
+        }
+        "desc" => parse_desc(args),
+        "list" => Ok(match args.first() {
+            Some(arg) if arg.eq_ignore_ascii_case("due") => Command::ListDue,
+            Some(column) => Command::List(Some(column.text.clone())),
+            None => Command::List(None),
+        }),
+        "column" => parse_column(args).map(Command::Column),
+        "prio" => {
+            let (task, value) = task_and_value(args, "Usage: prio <task-id> <low|med|high|urgent|none>")?;
+            let priority = optional(value, |v| parse_priority(v).map_err(at(v)))?;
+            Ok(Command::Priority { task, priority })
+        }
+        "due" => {
+            let (task, value) = task_and_value(args, "Usage: due <task-id> <today|tomorrow|fri|2026-11-03|+3d|none>")?;
+            let due = optional(value, |v| dates::parse_date(v, today).map_err(at(v)))?;
+            Ok(Command::Due { task, due })
+        }
+        "est" => {
+            let (task, value) = task_and_value(args, "Usage: est <task-id> <points|none>")?;
+            let estimate = optional(value, |v| parse_estimate(v).map_err(at(v)))?;
+            Ok(Command::Estimate { task, estimate })
+        }
+        "recur" => {
+            let (task, value) = task_and_value(
+                args,
+                "Usage: recur <task-id> <every day|every weekday|every mon,thu|every month on 1|none>",
+            )?;
+            // The rule is the rest of the line: "every month on 1"
+            let rule = optional(value, |_| {
+                let words = &args[1..];
+                Recurrence::parse(&join_words(words)).map_err(|message| CommandError::InvalidArgument {
+                    message,
+                    span: words[0].start..words[words.len() - 1].end,
+                })
+            })?;
+            Ok(Command::Recur { task, rule })
+        }
+        "lane" => {
+            let (task, value) = task_and_value(args, "Usage: lane <task-id> <name|none>")?;
+            let lane = optional(value, |v| parse_lane(v).map_err(at(v)))?;
+            Ok(Command::Lane { task, lane })
+        }
+        "lanes" => match args.first() {
+            Some(mode) => LaneMode::parse(mode)
+                .map(|mode| Command::Lanes(Some(mode)))
+                .ok_or_else(|| usage("Usage: lanes off|lane|tag|priority")),
+            None => Ok(Command::Lanes(None)),
+        },
+        "tag" => parse_tag_command(args).map(Command::Tag),
+        "check" => parse_check(args).map(Command::Check),
+        "history" => Ok(Command::History(task_arg(args, "Usage: history <task-id>")?)),
+        "block" => {
+            if args.len() < 3 || !args[1].eq_ignore_ascii_case("by") {
+                return Err(usage("Usage: block <task-id> by <blocker-id>"));
+            }
+            Ok(Command::Block {
+                task: task_id(&args[0])?,
+                by: task_id(&args[2])?,
+            })
+        }
+        "unblock" => {
+            const USAGE: &str = "Usage: unblock <task-id> [by <blocker-id>]";
+            let task = task_arg(args, USAGE)?;
+            let by = match args.get(1) {
+                Some(kw) if kw.eq_ignore_ascii_case("by") => {
+                    Some(task_id(args.get(2).ok_or_else(|| usage(USAGE))?)?)
+                }
+                Some(other) => return Err(unexpected(other)),
+                None => None,
+            };
+            Ok(Command::Unblock { task, by })
+        }
+        "start" => Ok(Command::Start(task_arg(args, "Usage: start <task-id>")?)),
+        "stop" => Ok(Command::Stop),
+        "time" => Ok(Command::Time(task_arg(args, "Usage: time <task-id>")?)),
+        "clear" => Ok(Command::Clear),
+        "archive" => {
+            let target = args
+                .first()
+                .ok_or_else(|| usage("Usage: archive <task-id> | archive <column> | archive list"))?;
+            // A number is a task ID, anything else is a column name
+            Ok(if target.eq_ignore_ascii_case("list") {
+                Command::ArchiveList
+            } else if let Ok(task) = parse_task_id(target) {
+                Command::ArchiveTask(task)
+            } else {
+                Command::ArchiveColumn(target.text.clone())
+            })
+        }
+        "trash" => parse_trash(args),
+        "restore" => Ok(Command::Restore(task_arg(args, "Usage: restore <task-id>")?)),
+        "board" => parse_board(args).map(Command::Board),
+        "alias" => parse_alias(args, input).map(Command::Alias),
+        "undo" => Ok(Command::Undo),
+        "redo" => Ok(Command::Redo),
+        "help" => Ok(Command::Help),
+        _ => Err(unknown()),
+    }
+}
+
+/// Usage: add "task title" [to column] [#tag1 #tag2] [!priority] [due:<date>] [lane:<name>] [~estimate]
+fn parse_add(args: &[Token], today: NaiveDate) -> Result<Command, CommandError> {
+    let (title, rest) = args.split_first().ok_or_else(|| {
+        usage("Usage: add \"task title\" [to column] [#tags] [!priority] [due:<date>] [lane:<name>] [~estimate]")
+    })?;
+    let mut task = NewTask {
+        title: title.text.clone(),
+        ..Default::default()
+    };
+
+    let mut i = 0;
+    while i < rest.len() {
+        let arg = &rest[i];
+        if arg.eq_ignore_ascii_case("to") && i + 1 < rest.len() {
+            task.column = Some(rest[i + 1].text.clone());
+            i += 2;
+            continue;
+        }
+        if let Some(tag) = arg.strip_marker("#") {
+            task.tags.push(tag.to_string());
+        } else if let Some(level) = arg.strip_marker("!") {
+            task.priority = Some(parse_priority(level).map_err(at(arg))?);
+        } else if let Some(date) = arg.strip_marker("due:") {
+            task.due = Some(dates::parse_date(date, today).map_err(at(arg))?);
+        } else if let Some(name) = arg.strip_marker("lane:") {
+            task.lane = Some(parse_lane(name).map_err(at(arg))?);
+        } else if let Some(value) = arg.strip_marker("~") {
+            task.estimate = Some(parse_estimate(value).map_err(at(arg))?);
+        } else {
+            return Err(unquoted(arg));
+        }
+        i += 1;
+    }
+
+    Ok(Command::Add(task))
+}
+
+/// Usage: move <task-id> to <column> [at <n>]
+///        move <task-id> up|down|top|bottom
+fn parse_move(args: &[Token]) -> Result<Command, CommandError> {
+    const USAGE: &str = "Usage: move <task-id> to <column> [at <n>] | up | down | top | bottom";
+    const USAGE_TO: &str = "Usage: move <task-id> to <column> [at <n>]";
+
+    if args.len() < 2 {
+        return Err(usage(USAGE));
+    }
+
+    let task = task_id(&args[0])?;
+    let shift = match args[1].to_lowercase().as_str() {
+        "to" => {
+            let column = args.get(2).ok_or_else(|| usage(USAGE_TO))?;
+            let index = match args.get(3) {
+                Some(kw) if kw.eq_ignore_ascii_case("at") => {
+                    Some(position(args.get(4).ok_or_else(|| usage(USAGE_TO))?)? - 1)
+                }
+                Some(other) => return Err(unexpected(other)),
+                None => None,
+            };
+            return Ok(Command::MoveTo {
+                task,
+                column: column.text.clone(),
+                index,
+            });
+        }
+        "up" => Shift::Up,
+        "down" => Shift::Down,
+        "top" => Shift::Top,
+        "bottom" => Shift::Bottom,
+        _ => return Err(usage(USAGE)),
+    };
+    Ok(Command::Shift { task, shift })
+}
+
+/// Usage: desc <task-id> "text"
+///        desc <task-id> +"appended line"
+///        desc <task-id> --clear
+fn parse_desc(args: &[Token]) -> Result<Command, CommandError> {
+    const USAGE: &str = "Usage: desc <task-id> \"text\" | +\"appended line\" | --clear";
+
+    if args.len() < 2 {
+        return Err(usage(USAGE));
+    }
+
+    let task = task_id(&args[0])?;
+    if args[1].is_flag("clear") {
+        return Ok(Command::Describe {
+            task,
+            change: DescriptionChange::Clear,
+        });
+    }
+
+    // A bare leading + appends instead of replacing, and unquoted text runs
+    // to the end of the line
+    let (append, first) = match args[1].strip_marker("+") {
+        Some(rest) => (true, rest),
+        None => (false, args[1].as_str()),
+    };
+    let words: Vec<&str> = std::iter::once(first)
+        .filter(|word| !word.is_empty())
+        .chain(args[2..].iter().map(Token::as_str))
+        .collect();
+    if words.is_empty() {
+        return Err(usage(USAGE));
+    }
+    let text = words.join(" ");
+
+    let change = if append {
+        DescriptionChange::Append(text)
+    } else {
+        DescriptionChange::Set(text)
+    };
+    Ok(Command::Describe { task, change })
+}
+
+/// Usage: trash [list]
+///        trash purge [--older-than 30d]
+fn parse_trash(args: &[Token]) -> Result<Command, CommandError> {
+    match args.first().map(|a| a.to_lowercase()).as_deref() {
+        None | Some("list" | "ls") => Ok(Command::TrashList),
+        Some("purge") => {
+            let older_than = match args.get(1) {
+                Some(flag) if flag.is_flag("older-than") => {
+                    let age = args
+                        .get(2)
+                        .ok_or_else(|| usage("Usage: trash purge [--older-than 30d]"))?;
+                    Some(dates::parse_age(age).map_err(at(age))?)
+                }
+                Some(other) => return Err(unexpected(other)),
+                None => None,
+            };
+            Ok(Command::PurgeTrash { older_than })
+        }
+        _ => Err(usage("Usage: trash [list] | trash purge [--older-than 30d]")),
+    }
+}
+
+/// Usage: tag list
+///        tag color <tag> <#rrggbb|none>
+///        tag rename <old> <new>
+///        tag merge <from> <into>
+///        tag add|rm <task-id> #tag...
+fn parse_tag_command(args: &[Token]) -> Result<TagCommand, CommandError> {
+    const USAGE: &str = "Usage: tag list | color <tag> <#rrggbb|none> | rename <old> <new> | merge <from> <into> | add|rm <id> #tag...";
+
+    let Some((subcommand, args)) = args.split_first() else {
+        return Err(usage(USAGE));
+    };
+
+    match subcommand.to_lowercase().as_str() {
+        "list" | "ls" => Ok(TagCommand::List),
+        "color" | "colour" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: tag color <tag> <#rrggbb|none>"));
+            }
+            let tag = tag(&args[0])?;
+            let color = optional(&args[1], |v| parse_color(v).map_err(at(v)))?;
+            Ok(TagCommand::Color { tag, color })
+        }
+        "rename" | "mv" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: tag rename <old> <new>"));
+            }
+            Ok(TagCommand::Rename {
+                old: tag(&args[0])?,
+                new: tag(&args[1])?,
+            })
+        }
+        "merge" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: tag merge <from> <into>"));
+            }
+            Ok(TagCommand::Merge {
+                from: tag(&args[0])?,
+                into: tag(&args[1])?,
+            })
+        }
+        "add" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: tag add <task-id> #tag..."));
+            }
+            Ok(TagCommand::Add {
+                task: task_id(&args[0])?,
+                tags: args[1..].iter().map(tag).collect::<Result<_, _>>()?,
+            })
+        }
+        "rm" | "remove" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: tag rm <task-id> #tag..."));
+            }
+            Ok(TagCommand::Remove {
+                task: task_id(&args[0])?,
+                tags: args[1..].iter().map(tag).collect::<Result<_, _>>()?,
+            })
+        }
+        other => Err(invalid(subcommand, format!("Unknown tag command: {}. {}", other, USAGE))),
+    }
+}
+
+/// Usage: check add <task-id> "step"
+///        check toggle <task-id> <n>
+///        check rm <task-id> <n>
+fn parse_check(args: &[Token]) -> Result<CheckCommand, CommandError> {
+    const USAGE: &str = "Usage: check add <id> \"step\" | check toggle <id> <n> | check rm <id> <n>";
+
+    if args.len() < 3 {
+        return Err(usage(USAGE));
+    }
+
+    let task = task_id(&args[1])?;
+    match args[0].to_lowercase().as_str() {
+        "add" | "a" => match args.get(3) {
+            Some(extra) => Err(unquoted(extra)),
+            None => Ok(CheckCommand::Add {
+                task,
+                text: args[2].text.clone(),
+            }),
+        },
+        "toggle" | "t" | "x" => Ok(CheckCommand::Toggle {
+            task,
+            index: position(&args[2])? - 1,
+        }),
+        "rm" | "remove" | "del" => Ok(CheckCommand::Remove {
+            task,
+            index: position(&args[2])? - 1,
+        }),
+        _ => Err(usage(USAGE)),
+    }
+}
+
+/// Usage: column add|rename|delete|move|sort|done|wip|wip-policy ...
+fn parse_column(args: &[Token]) -> Result<ColumnCommand, CommandError> {
+    const USAGE: &str = "Usage: column add|rename|delete|move|sort|done|wip|wip-policy ...";
+
+    let Some((subcommand, args)) = args.split_first() else {
+        return Err(usage(USAGE));
+    };
+    // Every subcommand but wip-policy starts with a column name, which the
+    // arms check is there before asking for it
+    let name = || args[0].text.clone();
+
+    match subcommand.to_lowercase().as_str() {
+        // column add <name>
+        "add" | "a" => {
+            if args.is_empty() {
+                return Err(usage("Usage: column add <name>"));
+            }
+            Ok(ColumnCommand::Add(name()))
+        }
+        // column rename <name> <new-name>
+        "rename" | "ren" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: column rename <name> <new-name>"));
+            }
+            Ok(ColumnCommand::Rename {
+                name: name(),
+                new_name: args[1].text.clone(),
+            })
+        }
+        // column delete <name> [--move-tasks-to <column>]
+        "delete" | "del" | "rm" => {
+            const USAGE_DELETE: &str = "Usage: column delete <name> [--move-tasks-to <column>]";
+            if args.is_empty() {
+                return Err(usage(USAGE_DELETE));
+            }
+            let move_tasks_to = match args.get(1) {
+                Some(flag) if flag.is_flag("move-tasks-to") => {
+                    Some(args.get(2).ok_or_else(|| usage(USAGE_DELETE))?.text.clone())
+                }
+                Some(other) => return Err(unexpected(other)),
+                None => None,
+            };
+            Ok(ColumnCommand::Delete {
+                name: name(),
+                move_tasks_to,
+            })
+        }
+        // column move <name> <position> (1 = leftmost)
+        "move" | "mv" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: column move <name> <position>"));
+            }
+            Ok(ColumnCommand::Move {
+                name: name(),
+                index: position(&args[1])? - 1,
+            })
+        }
+        // column sort <name> priority|manual
+        "sort" => {
+            let by_priority = match args.get(1).map(|m| m.to_lowercase()).as_deref() {
+                Some("priority" | "prio") => true,
+                Some("manual") => false,
+                _ => return Err(usage("Usage: column sort <name> priority|manual")),
+            };
+            Ok(ColumnCommand::Sort {
+                name: name(),
+                by_priority,
+            })
+        }
+        // column done <name> [off]
+        "done" => {
+            const USAGE_DONE: &str = "Usage: column done <name> [off]";
+            if args.is_empty() {
+                return Err(usage(USAGE_DONE));
+            }
+            let done = match args.get(1).map(|m| m.to_lowercase()).as_deref() {
+                None | Some("on") => true,
+                Some("off") => false,
+                _ => return Err(usage(USAGE_DONE)),
+            };
+            Ok(ColumnCommand::Done { name: name(), done })
+        }
+        // column wip <name> <limit|none>
+        "wip" => {
+            if args.len() < 2 {
+                return Err(usage("Usage: column wip <name> <limit|none>"));
+            }
+            Ok(ColumnCommand::Wip {
+                name: name(),
+                limit: optional(&args[1], position)?,
+            })
+        }
+        // column wip-policy warn|refuse
+        "wip-policy" => match args.first().map(|p| p.to_lowercase()).as_deref() {
+            Some("warn") => Ok(ColumnCommand::WipPolicy(WipPolicy::Warn)),
+            Some("refuse") => Ok(ColumnCommand::WipPolicy(WipPolicy::Refuse)),
+            _ => Err(usage("Usage: column wip-policy warn|refuse")),
+        },
+        other => Err(invalid(subcommand, format!("Unknown column command: {}. {}", other, USAGE))),
+    }
+}
+
+/// Usage: board list
+///        board new|switch|delete <name>
+///        board switch .
+///        board init
+fn parse_board(args: &[Token]) -> Result<BoardCommand, CommandError> {
+    let Some(subcommand) = args.first() else {
+        return Err(usage("Usage: board new|switch|list|delete <name> or board init"));
+    };
+
+    match subcommand.to_lowercase().as_str() {
+        "list" | "ls" => Ok(BoardCommand::List),
+        "new" | "create" => Ok(BoardCommand::New(board_name(args)?)),
+        "init" => Ok(BoardCommand::Init),
+        "switch" | "sw" | "open" => {
+            // "." is the board of the project we were started in
+            if args.get(1).is_some_and(|arg| arg.as_str() == ".") {
+                Ok(BoardCommand::SwitchProject)
+            } else {
+                Ok(BoardCommand::Switch(board_name(args)?))
+            }
+        }
+        "delete" | "del" | "rm" => Ok(BoardCommand::Delete(board_name(args)?)),
+        other => Err(invalid(subcommand, format!("Unknown board command: {}", other))),
+    }
+}
+
+/// Get and validate the board name argument
+fn board_name(args: &[Token]) -> Result<String, CommandError> {
+    let name = args.get(1).ok_or_else(|| usage("Expected a board name"))?;
+    persistence::validate_board_name(name).map_err(at(name))?;
+    Ok(name.text.clone())
+}
+
+/// Usage: alias [list]
+///        alias <name> = "<command>; <command>..."
+///        alias <name>
+///        alias rm <name>
+fn parse_alias(args: &[Token], input: &str) -> Result<AliasCommand, CommandError> {
+    const USAGE: &str = "Usage: alias <name> = \"<command>; <command>...\" | alias list | alias rm <name>";
+
+    let Some(name) = args.first() else {
+        return Ok(AliasCommand::List);
+    };
+    match (name.to_lowercase().as_str(), args.get(1)) {
+        ("list" | "ls", None) => return Ok(AliasCommand::List),
+        ("rm" | "remove" | "del", Some(alias)) if args.len() == 2 && alias.as_str() != "=" => {
+            return Ok(AliasCommand::Remove(alias.to_lowercase()));
+        }
+        _ => {}
+    }
+
+    alias::validate_name(name).map_err(at(name))?;
+    let name_text = name.to_lowercase();
+    let Some(equals) = args.get(1) else {
+        return Ok(AliasCommand::Show(name_text));
+    };
+    if equals.as_str() != "=" {
+        return Err(invalid(equals, format!("Expected '=' after the alias name. {}", USAGE)));
+    }
+
+    // One quoted word is the body, several unquoted ones are taken as typed
+    let body = match &args[2..] {
+        [] => return Err(usage(USAGE)),
+        [word] => word.text.clone(),
+        [first, .., last] => input.chars().skip(first.start).take(last.end - first.start).collect(),
+    };
+    alias::validate_body(&body).map_err(|message| CommandError::InvalidArgument {
+        message,
+        span: args[2].start..args[args.len() - 1].end,
+    })?;
+    Ok(AliasCommand::Define { name: name_text, body })
+}
+
+fn usage(message: impl Into<String>) -> CommandError {
+    CommandError::Usage(message.into())
+}
+
+/// An error pointing at `token`
+fn invalid(token: &Token, message: impl Into<String>) -> CommandError {
+    CommandError::InvalidArgument {
+        message: message.into(),
+        span: token.span(),
+    }
+}
+
+/// Point an error from one of the value parsers at the word it came from
+fn at(token: &Token) -> impl FnOnce(String) -> CommandError + '_ {
+    move |message| invalid(token, message)
+}
+
+fn unexpected(token: &Token) -> CommandError {
+    invalid(token, format!("Unexpected argument: {}", token))
+}
+
+/// A word left over after a one-word text, most likely text that needed quotes
+fn unquoted(token: &Token) -> CommandError {
+    invalid(token, format!("Unexpected argument: {} (quote text with spaces)", token))
+}
+
+/// Parse a value that can also be `none`
+fn optional<T>(
+    token: &Token,
+    parse: impl FnOnce(&Token) -> Result<T, CommandError>,
+) -> Result<Option<T>, CommandError> {
+    if token.eq_ignore_ascii_case("none") {
+        Ok(None)
+    } else {
+        parse(token).map(Some)
+    }
+}
+
+/// The task ID of a command taking just that
+fn task_arg(args: &[Token], usage_line: &str) -> Result<TaskId, CommandError> {
+    task_id(args.first().ok_or_else(|| usage(usage_line))?)
+}
+
+/// The task ID and the value of a `<command> <task-id> <value>` command
+fn task_and_value<'a>(args: &'a [Token], usage_line: &str) -> Result<(TaskId, &'a Token), CommandError> {
+    if args.len() < 2 {
+        return Err(usage(usage_line));
+    }
+    Ok((task_id(&args[0])?, &args[1]))
+}
+
+fn task_id(token: &Token) -> Result<TaskId, CommandError> {
+    parse_task_id(token).map_err(at(token))
+}
+
+fn position(token: &Token) -> Result<usize, CommandError> {
+    parse_position(token).map_err(at(token))
+}
+
+fn tag(token: &Token) -> Result<String, CommandError> {
+    parse_tag(token).map(str::to_string).map_err(at(token))
+}
+
+/// Join words back into text separated by single spaces
+fn join_words(args: &[Token]) -> String {
+    args.iter().map(Token::as_str).collect::<Vec<_>>().join(" ")
+}
+
+/// Parse an estimate: a non-negative number like 3 or 0.5
+fn parse_estimate(value: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|e| e.is_finite() && *e >= 0.0)
+        .ok_or_else(|| format!("Invalid estimate: {}", value))
+}
+
+/// Parse a tag, with or without its leading #
+fn parse_tag(tag: &str) -> Result<&str, String> {
+    let tag = tag.strip_prefix('#').unwrap_or(tag);
+    if tag.is_empty() {
+        return Err("Expected a tag".to_string());
+    }
+    Ok(tag)
+}
+
+/// Parse a hex color like #ff8800
+fn parse_color(hex: &str) -> Result<[u8; 3], String> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    let channel = |i: usize| digits.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+    match (digits.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok([r, g, b]),
+        _ => Err(format!("Invalid color: {} (use #rrggbb)", hex)),
+    }
+}
+
+/// Parse a lane name
+fn parse_lane(name: &str) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("Expected a lane name".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Parse a task ID (simple number)
+fn parse_task_id(id_str: &str) -> Result<TaskId, String> {
+    id_str.parse::<u32>()
+        .map_err(|_| format!("Invalid task ID: {}", id_str))
+}
+
+/// Parse a priority level
+fn parse_priority(level: &str) -> Result<Priority, String> {
+    Priority::parse(level).ok_or_else(|| {
+        format!("Invalid priority: {} (use low, med, high or urgent)", level)
+    })
+}
+
+/// Parse a 1-based position as typed by the user
+fn parse_position(pos_str: &str) -> Result<usize, String> {
+    match pos_str.parse::<usize>() {
+        Ok(pos) if pos >= 1 => Ok(pos),
+        _ => Err(format!("Invalid position: {} (positions start at 1)", pos_str)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday
+    fn wednesday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()
+    }
+
+    fn parse_err(input: &str) -> CommandError {
+        parse(input, wednesday()).unwrap_err()
+    }
+
+    #[test]
+    fn parses_add_with_options() {
+        let command = parse("add \"Fix login\" to Doing #bug !high due:fri ~3", wednesday()).unwrap();
+        assert_eq!(
+            command,
+            Command::Add(NewTask {
+                title: "Fix login".to_string(),
+                column: Some("Doing".to_string()),
+                tags: vec!["bug".to_string()],
+                priority: Some(Priority::High),
+                due: NaiveDate::from_ymd_opt(2026, 10, 16),
+                lane: None,
+                estimate: Some(3.0),
+            })
+        );
+    }
+
+    #[test]
+    fn relative_dates_count_from_the_given_day() {
+        let due = |input| match parse(input, wednesday()) {
+            Ok(Command::Due { due, .. }) => due,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(due("due 7 fri"), NaiveDate::from_ymd_opt(2026, 10, 16));
+        assert_eq!(due("due 7 +1w"), NaiveDate::from_ymd_opt(2026, 10, 21));
+        assert_eq!(due("due 7 none"), None);
+    }
+
+    #[test]
+    fn reports_unknown_commands_at_the_name() {
+        assert_eq!(
+            parse_err("  frobnicate 1"),
+            CommandError::UnknownCommand {
+                name: "frobnicate".to_string(),
+                span: 2..12,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_missing_arguments_as_usage() {
+        for input in ["add", "move 1", "edit 1", "delete", "block 1 2", "tag rm 1"] {
+            assert!(matches!(parse_err(input), CommandError::Usage(_)), "{}", input);
+        }
+        assert!(matches!(parse_err(""), CommandError::Usage(_)));
+    }
+
+    #[test]
+    fn reports_bad_arguments_where_they_are() {
+        let span = |input| parse_err(input).span();
+        assert_eq!(span("delete abc"), Some(7..10));
+        assert_eq!(span("prio 1 huge"), Some(7..11));
+        assert_eq!(span("move 1 to Doing at 0"), Some(19..20));
+        assert_eq!(span("due 1000 +-3d"), Some(9..13));
+        assert_eq!(span("add \"ünïcode\" !nope"), Some(14..19));
+    }
+
+    #[test]
+    fn rejects_words_that_needed_quotes() {
+        let err = parse_err("add \"a\" \"b\"");
+        assert_eq!(err.span(), Some(8..11));
+        assert_eq!(err.to_string(), "Unexpected argument: b (quote text with spaces)");
+        assert_eq!(parse_err("add Buy milk").span(), Some(8..12));
+        assert_eq!(parse_err("edit 3 New title").span(), Some(11..16));
+        assert_eq!(parse_err("check add 3 Write tests").span(), Some(18..23));
+    }
+
+    #[test]
+    fn reports_unclosed_quotes_as_syntax_errors() {
+        assert_eq!(
+            parse_err("add \"half"),
+            CommandError::Syntax(ParseError::new("Unclosed quote", 4))
+        );
+    }
+
+    #[test]
+    fn points_carets_at_the_error() {
+        assert_eq!(
+            parse_err("delete abc").display_with("delete abc"),
+            "Invalid task ID: abc\n  delete abc\n         ^^^"
+        );
+    }
+}
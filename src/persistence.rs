@@ -1,8 +1,15 @@
-use crate::state::KanbanState;
-use std::path::PathBuf;
+use crate::history::MAX_HISTORY;
+use crate::state::{KanbanState, DEFAULT_BOARD};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::sync::mpsc;
 
+/// Directory and file name of a project-local board, relative to the project root
+const PROJECT_BOARD_DIR: &str = ".kanban";
+const PROJECT_BOARD_FILE: &str = "board.json";
+
 /// Get the config directory path
 pub fn get_config_dir() -> PathBuf {
     let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -10,39 +17,162 @@ pub fn get_config_dir() -> PathBuf {
     path
 }
 
-/// Get the state file path
+/// Get the state file path (the default board)
 pub fn get_state_file() -> PathBuf {
     let mut path = get_config_dir();
     path.push("state.json");
     path
 }
 
-/// Load state from disk
+/// Directory holding every board except the default one
+fn get_boards_dir() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("boards");
+    path
+}
+
+/// File remembering which board was open last
+fn get_active_board_file() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("active_board");
+    path
+}
+
+/// File holding the command bar history, one command per line
+fn get_history_file() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("history");
+    path
+}
+
+/// File holding the user's command aliases
+fn get_aliases_file() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("aliases.json");
+    path
+}
+
+/// Get the file a board is stored in.
+/// The default board keeps using `state.json` so existing data carries over.
+pub fn get_board_file(name: &str) -> PathBuf {
+    if name == DEFAULT_BOARD {
+        return get_state_file();
+    }
+    let mut path = get_boards_dir();
+    path.push(format!("{}.json", name));
+    path
+}
+
+/// Where a board is saved: its project file, or its file in the config dir
+pub fn get_state_path(state: &KanbanState) -> PathBuf {
+    state
+        .board_path
+        .clone()
+        .unwrap_or_else(|| get_board_file(&state.board_name))
+}
+
+/// Search the working directory and its parents for `.kanban/board.json`,
+/// the same way git looks for `.git`
+pub fn find_project_board() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(PROJECT_BOARD_DIR).join(PROJECT_BOARD_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Where `board init` puts a new project board: the root of the enclosing
+/// git repository, or the working directory outside of one
+pub fn new_project_board_path() -> Result<PathBuf, String> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| format!("Failed to get working directory: {}", e))?;
+    let root = cwd
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(&cwd);
+    Ok(root.join(PROJECT_BOARD_DIR).join(PROJECT_BOARD_FILE))
+}
+
+/// Name shown for a project board: the name of its project directory
+pub fn project_board_name(path: &Path) -> String {
+    path.parent()
+        .and_then(|dir| dir.parent())
+        .and_then(|root| root.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string())
+}
+
+/// Check a board name is usable as a file name
+pub fn validate_board_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid board name: {} (use letters, digits, - and _)",
+            name
+        ))
+    }
+}
+
+/// Parse a board file and bring it up to date
+fn parse_board(contents: &str, name: &str) -> Result<KanbanState, String> {
+    let mut state: KanbanState = serde_json::from_str(contents)
+        .map_err(|e| format!("Failed to parse state file: {}", e))?;
+
+    state.board_name = name.to_string();
+    state.restore_column_order();
+    state.ensure_done_column();
+    let reassigned = state.repair_ids();
+    if reassigned > 0 {
+        state.set_status(format!("Reassigned {} duplicate IDs", reassigned));
+    }
+
+    Ok(state)
+}
+
+/// Load state from disk: the current project's board if there is one,
+/// otherwise the board that was open last
 pub async fn load_state() -> Result<KanbanState, String> {
-    let path = get_state_file();
+    if let Some(path) = find_project_board() {
+        let contents = fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("Failed to read state file: {}", e))?;
+        let mut state = parse_board(&contents, &project_board_name(&path))?;
+        state.board_path = Some(path);
+        return Ok(state);
+    }
+
+    let name = get_active_board();
+    let path = get_board_file(&name);
 
     if !path.exists() {
-        return Ok(KanbanState::default());
+        return Ok(KanbanState {
+            board_name: name,
+            ..Default::default()
+        });
     }
 
     let contents = fs::read_to_string(&path)
         .await
         .map_err(|e| format!("Failed to read state file: {}", e))?;
 
-    serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse state file: {}", e))
+    parse_board(&contents, &name)
 }
 
 /// Save state to disk (async)
 pub async fn save_state(state: &KanbanState) -> Result<(), String> {
-    let dir = get_config_dir();
+    let path = get_state_path(state);
 
     // Create directory if it doesn't exist
-    fs::create_dir_all(&dir)
-        .await
-        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
 
-    let path = get_state_file();
     let contents = serde_json::to_string_pretty(state)
         .map_err(|e| format!("Failed to serialize state: {}", e))?;
 
@@ -53,6 +183,155 @@ pub async fn save_state(state: &KanbanState) -> Result<(), String> {
     Ok(())
 }
 
+// Board management runs from commands on the UI thread, so it uses blocking
+// I/O. Board files are small and this only happens on explicit commands.
+
+/// Name of the board that was open last
+pub fn get_active_board() -> String {
+    std::fs::read_to_string(get_active_board_file())
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|name| validate_board_name(name).is_ok())
+        .unwrap_or_else(|| DEFAULT_BOARD.to_string())
+}
+
+/// Remember which board is open
+pub fn set_active_board(name: &str) -> Result<(), String> {
+    std::fs::create_dir_all(get_config_dir())
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    std::fs::write(get_active_board_file(), name)
+        .map_err(|e| format!("Failed to save active board: {}", e))
+}
+
+/// Names of all boards on disk, default first
+pub fn list_boards() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(get_boards_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    if path.extension().is_some_and(|ext| ext == "json") {
+                        path.file_stem().map(|stem| stem.to_string_lossy().to_string())
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, DEFAULT_BOARD.to_string());
+    names
+}
+
+/// Check whether a board has been saved
+pub fn board_exists(name: &str) -> bool {
+    get_board_file(name).exists()
+}
+
+/// Load a board by name (blocking)
+pub fn load_board_blocking(name: &str) -> Result<KanbanState, String> {
+    let path = get_board_file(name);
+    if !path.exists() {
+        if name == DEFAULT_BOARD {
+            return Ok(KanbanState::default());
+        }
+        return Err(format!("Board '{}' not found", name));
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read state file: {}", e))?;
+    parse_board(&contents, name)
+}
+
+/// Load a project board from its file (blocking)
+pub fn load_project_board_blocking(path: &Path) -> Result<KanbanState, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read state file: {}", e))?;
+    let mut state = parse_board(&contents, &project_board_name(path))?;
+    state.board_path = Some(path.to_path_buf());
+    Ok(state)
+}
+
+/// Save a board immediately (blocking)
+pub fn save_board_blocking(state: &KanbanState) -> Result<(), String> {
+    let path = get_state_path(state);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize state: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write state file: {}", e))
+}
+
+/// Delete a board's file
+pub fn delete_board(name: &str) -> Result<(), String> {
+    if name == DEFAULT_BOARD {
+        return Err("The default board cannot be deleted".to_string());
+    }
+    std::fs::remove_file(get_board_file(name))
+        .map_err(|e| format!("Failed to delete board '{}': {}", name, e))
+}
+
+/// Load the command history, oldest first (blocking). A file that has
+/// grown past the limit is cut back to it.
+pub fn load_history() -> Vec<String> {
+    let Ok(contents) = std::fs::read_to_string(get_history_file()) else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<String> = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect();
+    if entries.len() > MAX_HISTORY {
+        entries.drain(..entries.len() - MAX_HISTORY);
+        let mut contents = entries.join("\n");
+        contents.push('\n');
+        if let Err(e) = std::fs::write(get_history_file(), contents) {
+            eprintln!("Failed to trim history: {}", e);
+        }
+    }
+    entries
+}
+
+/// Add a command to the end of the history file (blocking)
+pub fn append_history(command: &str) -> Result<(), String> {
+    std::fs::create_dir_all(get_config_dir())
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(get_history_file())
+        .map_err(|e| format!("Failed to open history file: {}", e))?;
+    writeln!(file, "{}", command).map_err(|e| format!("Failed to write history: {}", e))
+}
+
+/// Load the command aliases, by name (blocking)
+pub fn load_aliases() -> BTreeMap<String, String> {
+    let Ok(contents) = std::fs::read_to_string(get_aliases_file()) else {
+        return BTreeMap::new();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        eprintln!("Failed to parse aliases: {}", e);
+        BTreeMap::new()
+    })
+}
+
+/// Save the command aliases (blocking)
+pub fn save_aliases(aliases: &BTreeMap<String, String>) -> Result<(), String> {
+    std::fs::create_dir_all(get_config_dir())
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let contents = serde_json::to_string_pretty(aliases)
+        .map_err(|e| format!("Failed to serialize aliases: {}", e))?;
+    std::fs::write(get_aliases_file(), contents)
+        .map_err(|e| format!("Failed to save aliases: {}", e))
+}
+
 /// Background saver that batches writes
 pub struct StateSaver {
     tx: mpsc::Sender<KanbanState>,
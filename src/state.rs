@@ -1,55 +1,286 @@
-use chrono::{DateTime, Utc};
+use crate::dates;
+use crate::history::CommandHistory;
+use crate::undo::UndoStack;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::PathBuf;
 
-/// A unique identifier for tasks (simple 4-digit number)
+/// A unique identifier for tasks (simple number, starting at 1000)
 pub type TaskId = u32;
 
 /// A unique identifier for columns
 pub type ColumnId = u32;
 
-/// Global counter for task IDs
-static NEXT_TASK_ID: AtomicU32 = AtomicU32::new(1000);
+/// Name of the board stored in the original `state.json`
+pub const DEFAULT_BOARD: &str = "default";
 
-fn next_task_id() -> TaskId {
-    let id = NEXT_TASK_ID.fetch_add(1, Ordering::SeqCst);
-    // Keep it within 4 digits (1000-9999)
-    if id > 9999 {
-        NEXT_TASK_ID.store(1000, Ordering::SeqCst);
-        1000
-    } else {
-        id
-    }
+/// First ID handed out on a fresh board
+const FIRST_TASK_ID: TaskId = 1000;
+const FIRST_COLUMN_ID: ColumnId = 1;
+
+fn default_next_task_id() -> TaskId {
+    FIRST_TASK_ID
+}
+
+fn default_next_column_id() -> ColumnId {
+    FIRST_COLUMN_ID
 }
 
 /// The main kanban board state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KanbanState {
+    /// Name of the board, which decides the file it is saved to
+    #[serde(skip)]
+    pub board_name: String,
+    /// File of a project-local board. Named boards live in the config dir.
+    #[serde(skip)]
+    pub board_path: Option<PathBuf>,
     pub columns: Vec<Column>,
+    /// Next task ID to hand out. Only ever grows, so deleted IDs are never reused.
+    #[serde(default = "default_next_task_id")]
+    pub next_task_id: TaskId,
+    /// Next column ID to hand out
+    #[serde(default = "default_next_column_id")]
+    pub next_column_id: ColumnId,
+    /// Finished tasks taken off the board
+    #[serde(default)]
+    pub archive: Vec<RemovedTask>,
+    /// Deleted tasks, restorable until purged
+    #[serde(default)]
+    pub trash: Vec<RemovedTask>,
+    /// What to do when a task would push a column over its WIP limit
+    #[serde(default)]
+    pub wip_policy: WipPolicy,
+    /// How tasks are grouped into swimlanes
+    #[serde(default)]
+    pub lane_mode: LaneMode,
+    /// Colors picked for tags. Tags without one use the default blue.
+    #[serde(default)]
+    pub tag_colors: BTreeMap<String, [u8; 3]>,
+    /// Lanes folded away in the UI, by lane key
+    #[serde(skip)]
+    pub collapsed_lanes: HashSet<Option<String>>,
     #[serde(skip)]
     pub command_input: String,
+    /// Commands run from the command bar, shared by every board
+    #[serde(skip)]
+    pub command_history: CommandHistory,
+    /// User-defined command aliases by name, shared by every board
+    #[serde(skip)]
+    pub aliases: BTreeMap<String, String>,
     #[serde(skip)]
     pub dragging: Option<TaskId>,
     #[serde(skip)]
     pub status_message: Option<String>,
+    #[serde(skip)]
+    pub undo: UndoStack,
+    /// Warnings raised by the last change, shown alongside its status message
+    #[serde(skip)]
+    pub notices: Vec<String>,
+    /// Task open in the detail editor, with its unsaved edits
+    #[serde(skip)]
+    pub editing: Option<TaskDraft>,
+    /// Set when a change is recorded, so the app knows to save even if it
+    /// came from the mouse rather than the keyboard
+    #[serde(skip)]
+    pub unsaved_changes: bool,
+}
+
+/// Edits to a task in the detail editor, applied all at once on save
+#[derive(Debug, Clone)]
+pub struct TaskDraft {
+    pub task_id: TaskId,
+    pub title: String,
+    pub description: String,
+    /// Tags as typed, space separated with or without #
+    pub tags: String,
+    pub column: String,
+}
+
+impl TaskDraft {
+    pub fn new(task: &Task, column: &str) -> Self {
+        Self {
+            task_id: task.id,
+            title: task.title.clone(),
+            description: task.description.clone().unwrap_or_default(),
+            tags: task.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" "),
+            column: column.to_string(),
+        }
+    }
+}
+
+/// A task that was archived or deleted, with enough context to restore it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedTask {
+    pub task: Task,
+    /// Column the task was in when it was removed
+    pub column: String,
+    pub removed_at: DateTime<Utc>,
+}
+
+/// How WIP limits are enforced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WipPolicy {
+    /// Allow the change but show a warning
+    #[default]
+    Warn,
+    /// Reject the change
+    Refuse,
+}
+
+/// What swimlanes the board is split into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaneMode {
+    /// No swimlanes, just columns
+    #[default]
+    Off,
+    /// By each task's lane field
+    Lane,
+    /// By each task's first tag
+    Tag,
+    /// By priority
+    Priority,
+}
+
+impl LaneMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "off" | "none" => Some(LaneMode::Off),
+            "lane" | "lanes" => Some(LaneMode::Lane),
+            "tag" | "tags" => Some(LaneMode::Tag),
+            "priority" | "prio" => Some(LaneMode::Priority),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LaneMode::Off => "off",
+            LaneMode::Lane => "lane",
+            LaneMode::Tag => "tag",
+            LaneMode::Priority => "priority",
+        }
+    }
+
+    /// Heading for the lane of tasks without a lane key
+    pub fn empty_lane_label(&self) -> &'static str {
+        match self {
+            LaneMode::Off | LaneMode::Lane => "No lane",
+            LaneMode::Tag => "Untagged",
+            LaneMode::Priority => "No priority",
+        }
+    }
 }
 
 impl Default for KanbanState {
     fn default() -> Self {
-        Self {
-            columns: vec![
-                Column::new("Todo"),
-                Column::new("Doing"),
-                Column::new("Done"),
-            ],
+        let mut state = Self {
+            board_name: DEFAULT_BOARD.to_string(),
+            board_path: None,
+            columns: Vec::new(),
+            next_task_id: FIRST_TASK_ID,
+            next_column_id: FIRST_COLUMN_ID,
+            archive: Vec::new(),
+            trash: Vec::new(),
+            wip_policy: WipPolicy::default(),
+            lane_mode: LaneMode::default(),
+            tag_colors: BTreeMap::new(),
+            collapsed_lanes: HashSet::new(),
             command_input: String::new(),
+            command_history: CommandHistory::default(),
+            aliases: BTreeMap::new(),
             dragging: None,
             status_message: None,
+            undo: UndoStack::default(),
+            notices: Vec::new(),
+            editing: None,
+            unsaved_changes: false,
+        };
+        for name in ["Todo", "Doing", "Done"] {
+            let _ = state.add_column(name);
         }
+        state.ensure_done_column();
+        state
     }
 }
 
 impl KanbanState {
+    /// Hand out a fresh task ID
+    fn allocate_task_id(&mut self) -> TaskId {
+        let id = self.next_task_id;
+        self.next_task_id += 1;
+        id
+    }
+
+    /// Hand out a fresh column ID
+    fn allocate_column_id(&mut self) -> ColumnId {
+        let id = self.next_column_id;
+        self.next_column_id += 1;
+        id
+    }
+
+    /// Reseed the ID counters from a loaded board and fix up duplicate IDs.
+    ///
+    /// Boards saved before IDs were owned by the board may have a missing
+    /// counter, or tasks that share an ID because the old global counter
+    /// restarted at 1000 on every launch. The first task keeps its ID and
+    /// later duplicates get fresh ones. Returns the number of IDs reassigned.
+    pub fn repair_ids(&mut self) -> usize {
+        // Archived and trashed tasks keep their IDs reserved too
+        let max_task_id = self
+            .columns
+            .iter()
+            .flat_map(|c| c.tasks.iter())
+            .chain(self.archive.iter().map(|r| &r.task))
+            .chain(self.trash.iter().map(|r| &r.task))
+            .map(|t| t.id)
+            .max();
+        if let Some(max) = max_task_id {
+            self.next_task_id = self.next_task_id.max(max + 1);
+        }
+        self.next_task_id = self.next_task_id.max(FIRST_TASK_ID);
+
+        if let Some(max) = self.columns.iter().map(|c| c.id).max() {
+            self.next_column_id = self.next_column_id.max(max + 1);
+        }
+
+        let mut reassigned = 0;
+
+        let mut seen_tasks = HashSet::new();
+        let mut next_task_id = self.next_task_id;
+        let all_tasks = self
+            .columns
+            .iter_mut()
+            .flat_map(|c| c.tasks.iter_mut())
+            .chain(self.archive.iter_mut().map(|r| &mut r.task))
+            .chain(self.trash.iter_mut().map(|r| &mut r.task));
+        for task in all_tasks {
+            if !seen_tasks.insert(task.id) {
+                task.id = next_task_id;
+                next_task_id += 1;
+                seen_tasks.insert(task.id);
+                reassigned += 1;
+            }
+        }
+        self.next_task_id = next_task_id;
+
+        let mut seen_columns = HashSet::new();
+        for col_idx in 0..self.columns.len() {
+            if !seen_columns.insert(self.columns[col_idx].id) {
+                let new_id = self.allocate_column_id();
+                self.columns[col_idx].id = new_id;
+                seen_columns.insert(new_id);
+                reassigned += 1;
+            }
+        }
+
+        reassigned
+    }
+
     /// Find a column by name (case-insensitive)
     pub fn find_column_by_name(&mut self, name: &str) -> Option<&mut Column> {
         self.columns
@@ -57,8 +288,14 @@ impl KanbanState {
             .find(|c| c.name.eq_ignore_ascii_case(name))
     }
 
+    /// Find a column's index by name (case-insensitive)
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
     /// Find a task by ID across all columns
-    #[allow(dead_code)]
     pub fn find_task(&self, task_id: TaskId) -> Option<(ColumnId, &Task)> {
         for column in &self.columns {
             if let Some(task) = column.tasks.iter().find(|t| t.id == task_id) {
@@ -68,67 +305,988 @@ impl KanbanState {
         None
     }
 
-    /// Move a task from one column to another
-    pub fn move_task(&mut self, task_id: TaskId, target_column_name: &str) -> Result<(), String> {
-        // Find source column and task
-        let mut task_to_move = None;
+    /// Find a task by ID for modification
+    pub fn find_task_mut(&mut self, task_id: TaskId) -> Result<&mut Task, String> {
+        self.columns
+            .iter_mut()
+            .flat_map(|c| c.tasks.iter_mut())
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| "Task not found".to_string())
+    }
+
+    /// Find which column a task is in and where, as (column index, task index)
+    pub fn task_position(&self, task_id: TaskId) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(col_idx, column)| {
+            column
+                .tasks
+                .iter()
+                .position(|t| t.id == task_id)
+                .map(|pos| (col_idx, pos))
+        })
+    }
 
-        for column in &mut self.columns {
-            if let Some(pos) = column.tasks.iter().position(|t| t.id == task_id) {
-                task_to_move = Some(column.tasks.remove(pos));
-                break;
+    /// Move a task to a column at a specific index, or to the end if `index` is None.
+    ///
+    /// The index is the task's final position in the target column (clamped),
+    /// so moving within the same column works the same way as between columns.
+    pub fn move_task(
+        &mut self,
+        task_id: TaskId,
+        target_column_name: &str,
+        index: Option<usize>,
+    ) -> Result<(), String> {
+        // Look up both ends before touching anything so a bad column name
+        // doesn't lose the task
+        let (source_idx, pos) = self.task_position(task_id).ok_or("Task not found")?;
+        let target_idx = self
+            .column_index(target_column_name)
+            .ok_or_else(|| format!("Column '{}' not found", target_column_name))?;
+
+        if source_idx != target_idx {
+            self.check_wip_limit(target_idx)?;
+        }
+
+        let mut task = self.columns[source_idx].tasks.remove(pos);
+
+        // Work on the task stops once it's out of progress
+        if source_idx != target_idx && !self.is_in_progress_column(target_idx) {
+            if let Some(spent) = task.stop_timer() {
+                self.notify(format!(
+                    "Stopped timer on task {} ({})",
+                    task_id,
+                    dates::format_duration(spent)
+                ));
             }
         }
 
-        let task = task_to_move.ok_or("Task not found")?;
+        if source_idx != target_idx {
+            task.record(TaskEventKind::Moved {
+                from: self.columns[source_idx].name.clone(),
+                to: self.columns[target_idx].name.clone(),
+            });
+        }
 
-        // Find target column and add task
-        let target_column = self
-            .find_column_by_name(target_column_name)
-            .ok_or_else(|| format!("Column '{}' not found", target_column_name))?;
+        // Starting work on a blocked task is allowed, but worth pointing out
+        if source_idx != target_idx && self.is_in_progress_column(target_idx) {
+            let blockers = self.open_blockers(&task);
+            if !blockers.is_empty() {
+                let ids: Vec<String> = blockers.iter().map(|id| id.to_string()).collect();
+                self.notify(format!("Task {} is blocked by {}", task_id, ids.join(", ")));
+            }
+        }
+
+        let target_tasks = &mut self.columns[target_idx].tasks;
+        let index = index.unwrap_or(target_tasks.len()).min(target_tasks.len());
+        target_tasks.insert(index, task);
+        self.columns[target_idx].apply_sort();
+
+        if self.columns[target_idx].done && !self.columns[source_idx].done {
+            self.spawn_next_occurrence(task_id);
+        }
+        Ok(())
+    }
+
+    /// Blockers of a task that aren't finished yet. A blocker stops counting
+    /// once it reaches a done column or leaves the board.
+    pub fn open_blockers(&self, task: &Task) -> Vec<TaskId> {
+        task.blocked_by
+            .iter()
+            .copied()
+            .filter(|&id| {
+                self.task_position(id)
+                    .is_some_and(|(col_idx, _)| !self.columns[col_idx].done)
+            })
+            .collect()
+    }
+
+    /// Record that a task can't go ahead until another one is done
+    pub fn add_blocker(&mut self, task_id: TaskId, blocker_id: TaskId) -> Result<(), String> {
+        if task_id == blocker_id {
+            return Err("A task can't block itself".to_string());
+        }
+        if self.find_task(blocker_id).is_none() {
+            return Err(format!("Task {} not found", blocker_id));
+        }
+        if self.find_task(task_id).ok_or("Task not found")?.1.blocked_by.contains(&blocker_id) {
+            return Err(format!("Task {} is already blocked by {}", task_id, blocker_id));
+        }
+        if self.depends_on(blocker_id, task_id) {
+            return Err(format!(
+                "Task {} already depends on {}, that would be a cycle",
+                blocker_id, task_id
+            ));
+        }
+
+        let task = self.find_task_mut(task_id)?;
+        task.blocked_by.push(blocker_id);
+        task.record(TaskEventKind::Blocked { by: blocker_id });
+        Ok(())
+    }
+
+    /// Remove one blocker from a task, or all of them. Returns how many were removed.
+    pub fn remove_blocker(&mut self, task_id: TaskId, blocker_id: Option<TaskId>) -> Result<usize, String> {
+        let task = self.find_task_mut(task_id)?;
+        let removed: Vec<TaskId> = task
+            .blocked_by
+            .iter()
+            .copied()
+            .filter(|&id| blocker_id.is_none_or(|b| b == id))
+            .collect();
+        if removed.is_empty() {
+            return match blocker_id {
+                Some(id) => Err(format!("Task {} is not blocked by {}", task_id, id)),
+                None => Err(format!("Task {} has no blockers", task_id)),
+            };
+        }
+
+        task.blocked_by.retain(|id| !removed.contains(id));
+        for by in &removed {
+            task.record(TaskEventKind::Unblocked { by: *by });
+        }
+        Ok(removed.len())
+    }
+
+    /// Whether `task_id` depends on `target`, directly or through other tasks
+    fn depends_on(&self, task_id: TaskId, target: TaskId) -> bool {
+        let mut seen = HashSet::new();
+        let mut stack = vec![task_id];
+        while let Some(id) = stack.pop() {
+            if id == target {
+                return true;
+            }
+            if !seen.insert(id) {
+                continue;
+            }
+            if let Some((_, task)) = self.find_task(id) {
+                stack.extend(task.blocked_by.iter().copied());
+            }
+        }
+        false
+    }
+
+    /// A recurring task was finished: add a fresh copy to the first column,
+    /// due on the rule's next date. The rule moves to the copy, so moving the
+    /// finished task around again doesn't spawn more.
+    fn spawn_next_occurrence(&mut self, task_id: TaskId) {
+        let Some((col_idx, pos)) = self.task_position(task_id) else {
+            return;
+        };
+        let Some(rule) = self.columns[col_idx].tasks[pos].recurrence.take() else {
+            return;
+        };
+
+        // Count from the due date, or from today if that has already passed
+        let today = dates::today();
+        let finished = &self.columns[col_idx].tasks[pos];
+        let from = finished.due.map_or(today, |due| due.max(today));
+        let due = rule.next_after(from);
+
+        let next_id = self.allocate_task_id();
+        let finished = &mut self.columns[col_idx].tasks[pos];
+        finished.record(TaskEventKind::Recurred { next: next_id });
+
+        let mut next = Task::new(next_id, finished.title.clone(), finished.tags.clone());
+        next.description = finished.description.clone();
+        next.priority = finished.priority;
+        next.lane = finished.lane.clone();
+        next.estimate = finished.estimate;
+        next.checklist = finished
+            .checklist
+            .iter()
+            .map(|item| ChecklistItem { text: item.text.clone(), done: false })
+            .collect();
+        next.due = Some(due);
+        next.recurrence = Some(rule);
+        next.record(TaskEventKind::Created {
+            column: self.columns[0].name.clone(),
+        });
+
+        self.notify(format!("Task {} repeats: added {} due {}", task_id, next_id, due));
+        // The finished task has already moved, so a full column only warns
+        self.warn_wip_limit(0);
+        self.columns[0].tasks.push(next);
+        self.columns[0].apply_sort();
+    }
+
+    /// The task whose timer is running and when it started
+    pub fn running_timer(&self) -> Option<(&Task, DateTime<Utc>)> {
+        self.columns
+            .iter()
+            .flat_map(|c| c.tasks.iter())
+            .find_map(|t| t.timer_started().map(|start| (t, start)))
+    }
+
+    /// Start timing a task. Only one timer runs at a time, so a timer on
+    /// another task is stopped first; its ID is returned.
+    pub fn start_timer(&mut self, task_id: TaskId) -> Result<Option<TaskId>, String> {
+        if self.find_task(task_id).ok_or("Task not found")?.1.timer_started().is_some() {
+            return Err(format!("Timer is already running on task {}", task_id));
+        }
+
+        let stopped = self.stop_timer().ok().map(|(id, _)| id);
+        self.find_task_mut(task_id)?.time_entries.push(TimeEntry {
+            start: Utc::now(),
+            end: None,
+        });
+        Ok(stopped)
+    }
+
+    /// Stop the running timer, returning its task and how long it ran
+    pub fn stop_timer(&mut self) -> Result<(TaskId, Duration), String> {
+        let task_id = self.running_timer().ok_or("No timer is running")?.0.id;
+        let spent = self
+            .find_task_mut(task_id)?
+            .stop_timer()
+            .ok_or("No timer is running")?;
+        Ok((task_id, spent))
+    }
+
+    /// Every tag in use on the board or given a color, sorted
+    pub fn known_tags(&self) -> BTreeSet<String> {
+        self.all_tasks()
+            .flat_map(|t| t.tags.iter().cloned())
+            .chain(self.tag_colors.keys().cloned())
+            .collect()
+    }
+
+    /// Tasks on the board, in the archive and in the trash
+    fn all_tasks(&self) -> impl Iterator<Item = &Task> {
+        self.columns
+            .iter()
+            .flat_map(|c| c.tasks.iter())
+            .chain(self.archive.iter().map(|r| &r.task))
+            .chain(self.trash.iter().map(|r| &r.task))
+    }
+
+    /// Mutable version of `all_tasks`
+    fn all_tasks_mut(&mut self) -> impl Iterator<Item = &mut Task> {
+        self.columns
+            .iter_mut()
+            .flat_map(|c| c.tasks.iter_mut())
+            .chain(self.archive.iter_mut().map(|r| &mut r.task))
+            .chain(self.trash.iter_mut().map(|r| &mut r.task))
+    }
 
-        target_column.tasks.push(task);
+    /// Tag a task. Returns false if it already had the tag.
+    pub fn add_tag(&mut self, task_id: TaskId, tag: &str) -> Result<bool, String> {
+        let task = self.find_task_mut(task_id)?;
+        if task.tags.iter().any(|t| t == tag) {
+            return Ok(false);
+        }
+        task.tags.push(tag.to_string());
+        task.record(TaskEventKind::Tagged { tag: tag.to_string() });
+        Ok(true)
+    }
+
+    /// Remove a tag from a task
+    pub fn remove_tag(&mut self, task_id: TaskId, tag: &str) -> Result<(), String> {
+        let task = self.find_task_mut(task_id)?;
+        let pos = task
+            .tags
+            .iter()
+            .position(|t| t == tag)
+            .ok_or_else(|| format!("Task {} isn't tagged #{}", task_id, tag))?;
+        task.tags.remove(pos);
+        task.record(TaskEventKind::Untagged { tag: tag.to_string() });
+        Ok(())
+    }
+
+    /// Set or clear a tag's color
+    pub fn set_tag_color(&mut self, tag: &str, color: Option<[u8; 3]>) -> Result<(), String> {
+        if !self.known_tags().contains(tag) {
+            return Err(format!("Unknown tag: #{}", tag));
+        }
+        match color {
+            Some(color) => self.tag_colors.insert(tag.to_string(), color),
+            None => self.tag_colors.remove(tag),
+        };
+        Ok(())
+    }
+
+    /// Rename a tag on every task, archived and trashed ones included.
+    /// Returns how many tasks were changed.
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> Result<usize, String> {
+        let known = self.known_tags();
+        if !known.contains(old) {
+            return Err(format!("Unknown tag: #{}", old));
+        }
+        if known.contains(new) {
+            return Err(format!("#{} already exists, use tag merge to combine them", new));
+        }
+
+        let mut count = 0;
+        for task in self.all_tasks_mut() {
+            if let Some(tag) = task.tags.iter_mut().find(|t| *t == old) {
+                *tag = new.to_string();
+                task.record(TaskEventKind::Untagged { tag: old.to_string() });
+                task.record(TaskEventKind::Tagged { tag: new.to_string() });
+                count += 1;
+            }
+        }
+        if let Some(color) = self.tag_colors.remove(old) {
+            self.tag_colors.insert(new.to_string(), color);
+        }
+        Ok(count)
+    }
+
+    /// Fold tag `from` into `into` on every task. `into` keeps its color,
+    /// or takes over the color of `from` if it has none.
+    /// Returns how many tasks were changed.
+    pub fn merge_tags(&mut self, from: &str, into: &str) -> Result<usize, String> {
+        let known = self.known_tags();
+        for tag in [from, into] {
+            if !known.contains(tag) {
+                return Err(format!("Unknown tag: #{}", tag));
+            }
+        }
+        if from == into {
+            return Err("Can't merge a tag into itself".to_string());
+        }
+
+        let mut count = 0;
+        for task in self.all_tasks_mut() {
+            let Some(pos) = task.tags.iter().position(|t| t == from) else {
+                continue;
+            };
+            task.record(TaskEventKind::Untagged { tag: from.to_string() });
+            if task.tags.iter().any(|t| t == into) {
+                task.tags.remove(pos);
+            } else {
+                task.tags[pos] = into.to_string();
+                task.record(TaskEventKind::Tagged { tag: into.to_string() });
+            }
+            count += 1;
+        }
+        if let Some(color) = self.tag_colors.remove(from) {
+            self.tag_colors.entry(into.to_string()).or_insert(color);
+        }
+        Ok(count)
+    }
+
+    /// Set or clear a task's description
+    pub fn set_description(&mut self, task_id: TaskId, description: Option<String>) -> Result<(), String> {
+        self.find_task_mut(task_id)?.description = description;
+        Ok(())
+    }
+
+    /// Add text to the end of a task's description, on a new line
+    pub fn append_description(&mut self, task_id: TaskId, text: &str) -> Result<(), String> {
+        let task = self.find_task_mut(task_id)?;
+        match &mut task.description {
+            Some(description) => {
+                description.push('\n');
+                description.push_str(text);
+            }
+            None => task.description = Some(text.to_string()),
+        }
+        Ok(())
+    }
+
+    /// Set or clear a task's estimate
+    pub fn set_estimate(&mut self, task_id: TaskId, estimate: Option<f32>) -> Result<(), String> {
+        self.find_task_mut(task_id)?.estimate = estimate;
+        Ok(())
+    }
+
+    /// Set or clear a task's recurrence rule
+    pub fn set_recurrence(&mut self, task_id: TaskId, rule: Option<Recurrence>) -> Result<(), String> {
+        self.find_task_mut(task_id)?.recurrence = rule;
+        Ok(())
+    }
+
+    /// Check a column's WIP limit before one more task goes into it.
+    /// Depending on the policy this either refuses or records a notice.
+    fn check_wip_limit(&mut self, col_idx: usize) -> Result<(), String> {
+        let column = &self.columns[col_idx];
+        let Some(limit) = column.wip_limit else {
+            return Ok(());
+        };
+        if column.tasks.len() < limit {
+            return Ok(());
+        }
+
+        match self.wip_policy {
+            WipPolicy::Refuse => Err(format!(
+                "{} is at its WIP limit ({}/{})",
+                column.name,
+                column.tasks.len(),
+                limit
+            )),
+            WipPolicy::Warn => {
+                self.warn_wip_limit(col_idx);
+                Ok(())
+            }
+        }
+    }
+
+    /// Record a notice if one more task breaks a column's WIP limit, whatever
+    /// the policy. For tasks the board adds by itself, which can't be refused.
+    fn warn_wip_limit(&mut self, col_idx: usize) {
+        let column = &self.columns[col_idx];
+        let count = column.tasks.len() + 1;
+        if let Some(limit) = column.wip_limit.filter(|&limit| count > limit) {
+            let message = format!("{} is over its WIP limit ({}/{})", column.name, count, limit);
+            self.notify(message);
+        }
+    }
+
+    /// Set or clear a column's WIP limit
+    pub fn set_wip_limit(&mut self, name: &str, limit: Option<usize>) -> Result<(), String> {
+        let column = self
+            .find_column_by_name(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+        column.wip_limit = limit;
         Ok(())
     }
 
     /// Add a new task to a column
     pub fn add_task(&mut self, title: String, column_name: &str, tags: Vec<String>) -> Result<TaskId, String> {
-        let column = self
-            .find_column_by_name(column_name)
+        let col_idx = self
+            .column_index(column_name)
             .ok_or_else(|| format!("Column '{}' not found", column_name))?;
 
-        let task = Task::new(title, tags);
-        let task_id = task.id;
-        column.tasks.push(task);
+        self.check_wip_limit(col_idx)?;
+
+        let task_id = self.allocate_task_id();
+        let mut task = Task::new(task_id, title, tags);
+        task.record(TaskEventKind::Created {
+            column: self.columns[col_idx].name.clone(),
+        });
+        for tag in task.tags.clone() {
+            task.record(TaskEventKind::Tagged { tag });
+        }
+        self.columns[col_idx].tasks.push(task);
+        self.columns[col_idx].apply_sort();
         Ok(task_id)
     }
 
-    /// Delete a task by ID
+    /// Take a task off the board, recording where it was
+    fn take_task(&mut self, task_id: TaskId, event: TaskEventKind) -> Result<RemovedTask, String> {
+        let (col_idx, pos) = self.task_position(task_id).ok_or("Task not found")?;
+        let mut task = self.columns[col_idx].tasks.remove(pos);
+        task.record(event);
+        task.stop_timer();
+        Ok(RemovedTask {
+            task,
+            column: self.columns[col_idx].name.clone(),
+            removed_at: Utc::now(),
+        })
+    }
+
+    /// Delete a task by ID. Deleted tasks go to the trash and can be restored.
     pub fn delete_task(&mut self, task_id: TaskId) -> Result<(), String> {
-        for column in &mut self.columns {
-            if let Some(pos) = column.tasks.iter().position(|t| t.id == task_id) {
-                column.tasks.remove(pos);
-                return Ok(());
+        let removed = self.take_task(task_id, TaskEventKind::Trashed)?;
+        self.trash.push(removed);
+        Ok(())
+    }
+
+    /// Move every task on the board to the trash, returning how many were moved
+    pub fn clear_tasks(&mut self) -> usize {
+        let task_ids: Vec<TaskId> = self
+            .columns
+            .iter()
+            .flat_map(|c| c.tasks.iter().map(|t| t.id))
+            .collect();
+        for &task_id in &task_ids {
+            let _ = self.delete_task(task_id);
+        }
+        task_ids.len()
+    }
+
+    /// Archive a finished task
+    pub fn archive_task(&mut self, task_id: TaskId) -> Result<(), String> {
+        let removed = self.take_task(task_id, TaskEventKind::Archived)?;
+        self.archive.push(removed);
+        Ok(())
+    }
+
+    /// Archive every task in a column, returning how many were archived
+    pub fn archive_column(&mut self, name: &str) -> Result<usize, String> {
+        let col_idx = self
+            .column_index(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+        let task_ids: Vec<TaskId> = self.columns[col_idx].tasks.iter().map(|t| t.id).collect();
+        for &task_id in &task_ids {
+            self.archive_task(task_id)?;
+        }
+        Ok(task_ids.len())
+    }
+
+    /// Bring a task back from the trash or archive into the column it was
+    /// removed from (or the first column if that one is gone).
+    /// Returns the name of the column it was restored to.
+    pub fn restore_task(&mut self, task_id: TaskId) -> Result<String, String> {
+        let (in_trash, pos) = if let Some(pos) = self.trash.iter().position(|r| r.task.id == task_id) {
+            (true, pos)
+        } else if let Some(pos) = self.archive.iter().position(|r| r.task.id == task_id) {
+            (false, pos)
+        } else {
+            return Err(format!("Task {} is not in the trash or archive", task_id));
+        };
+
+        let list = if in_trash { &self.trash } else { &self.archive };
+        if self.columns.is_empty() {
+            return Err("Board has no columns".to_string());
+        }
+        let col_idx = self.column_index(&list[pos].column).unwrap_or(0);
+        // A refused restore leaves the task where it was
+        self.check_wip_limit(col_idx)?;
+
+        let removed = if in_trash { self.trash.remove(pos) } else { self.archive.remove(pos) };
+        let column = &mut self.columns[col_idx];
+        let mut task = removed.task;
+        task.record(TaskEventKind::Restored {
+            column: column.name.clone(),
+        });
+        column.tasks.push(task);
+        column.apply_sort();
+        Ok(column.name.clone())
+    }
+
+    /// Permanently delete trashed tasks, optionally only those trashed
+    /// longer ago than `older_than`. Returns how many were purged.
+    pub fn purge_trash(&mut self, older_than: Option<chrono::Duration>) -> usize {
+        let before = self.trash.len();
+        match older_than {
+            Some(age) => {
+                // An age reaching back past the earliest date covers nothing
+                if let Some(cutoff) = Utc::now().checked_sub_signed(age) {
+                    self.trash.retain(|r| r.removed_at > cutoff);
+                }
             }
+            None => self.trash.clear(),
         }
-        Err("Task not found".to_string())
+        before - self.trash.len()
     }
 
     /// Edit a task's title
     pub fn edit_task_title(&mut self, task_id: TaskId, new_title: String) -> Result<(), String> {
+        let task = self.find_task_mut(task_id)?;
+        if task.title != new_title {
+            let from = std::mem::replace(&mut task.title, new_title.clone());
+            task.record(TaskEventKind::TitleEdited { from, to: new_title });
+        }
+        Ok(())
+    }
+
+    /// Open a task in the detail editor
+    pub fn open_editor(&mut self, task_id: TaskId) -> Result<(), String> {
+        let (col_idx, pos) = self.task_position(task_id).ok_or("Task not found")?;
+        let column = &self.columns[col_idx];
+        self.editing = Some(TaskDraft::new(&column.tasks[pos], &column.name));
+        Ok(())
+    }
+
+    /// Apply the detail editor's edits to a task: title, description, tags
+    /// and column, recording history like the matching commands do
+    pub fn apply_draft(&mut self, draft: &TaskDraft) -> Result<(), String> {
+        let task_id = draft.task_id;
+        let title = draft.title.trim();
+        if title.is_empty() {
+            return Err("Title can't be empty".to_string());
+        }
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in draft.tags.split_whitespace() {
+            let tag = tag.trim_start_matches('#');
+            if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+
+        // Move first: it's the one edit that can be refused
+        let (col_idx, _) = self.task_position(task_id).ok_or("Task not found")?;
+        if !self.columns[col_idx].name.eq_ignore_ascii_case(&draft.column) {
+            self.move_task(task_id, &draft.column, None)?;
+        }
+
+        self.edit_task_title(task_id, title.to_string())?;
+
+        let task = self.find_task_mut(task_id)?;
+        let description = draft.description.trim_end();
+        task.description = (!description.is_empty()).then(|| description.to_string());
+
+        let removed: Vec<String> = task.tags.iter().filter(|t| !tags.contains(t)).cloned().collect();
+        let added: Vec<String> = tags.iter().filter(|t| !task.tags.contains(t)).cloned().collect();
+        for tag in removed {
+            task.record(TaskEventKind::Untagged { tag });
+        }
+        for tag in added {
+            task.record(TaskEventKind::Tagged { tag });
+        }
+        task.tags = tags;
+        Ok(())
+    }
+
+    /// Serialize the persisted part of the board (used for undo snapshots)
+    pub fn snapshot(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Record an undo step if the board changed since `before` was taken
+    pub fn record_undo(&mut self, before: String, label: &str) {
+        if self.snapshot() != before {
+            self.undo.push(label.to_string(), before);
+            self.unsaved_changes = true;
+        }
+    }
+
+    /// Revert the most recent change
+    pub fn undo(&mut self) -> Result<String, String> {
+        let (label, board) = self.undo.pop_undo().ok_or("Nothing to undo")?;
+        let current = self.snapshot();
+        self.restore_snapshot(&board)?;
+        self.undo.push_redo(label.clone(), current);
+        Ok(format!("Undid: {}", label))
+    }
+
+    /// Re-apply the most recently undone change
+    pub fn redo(&mut self) -> Result<String, String> {
+        let (label, board) = self.undo.pop_redo().ok_or("Nothing to redo")?;
+        let current = self.snapshot();
+        self.restore_snapshot(&board)?;
+        self.undo.push_undo(label.clone(), current);
+        Ok(format!("Redid: {}", label))
+    }
+
+    /// Put the board back the way it was when `before` was taken, without
+    /// recording anything for undo. For a change that failed halfway.
+    pub fn roll_back(&mut self, before: &str) {
+        if self.snapshot() != before {
+            if let Err(e) = self.restore_snapshot(before) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+
+    /// Replace the board with a snapshot, keeping UI-only state
+    fn restore_snapshot(&mut self, board: &str) -> Result<(), String> {
+        let mut restored: KanbanState = serde_json::from_str(board)
+            .map_err(|e| format!("Failed to restore snapshot: {}", e))?;
+
+        // IDs handed out after the snapshot stay used
+        restored.next_task_id = restored.next_task_id.max(self.next_task_id);
+        restored.next_column_id = restored.next_column_id.max(self.next_column_id);
+
+        restored.board_name = std::mem::take(&mut self.board_name);
+        restored.board_path = self.board_path.take();
+        restored.collapsed_lanes = std::mem::take(&mut self.collapsed_lanes);
+        restored.command_input = std::mem::take(&mut self.command_input);
+        restored.command_history = std::mem::take(&mut self.command_history);
+        restored.aliases = std::mem::take(&mut self.aliases);
+        restored.undo = std::mem::take(&mut self.undo);
+        *self = restored;
+        Ok(())
+    }
+
+    /// Replace this board with another one, keeping what's typed in the
+    /// command bar, its history and aliases. Undo history belongs to the old
+    /// board and is dropped.
+    pub fn switch_to(&mut self, mut board: KanbanState) {
+        board.command_input = std::mem::take(&mut self.command_input);
+        board.command_history = std::mem::take(&mut self.command_history);
+        board.aliases = std::mem::take(&mut self.aliases);
+        *self = board;
+    }
+
+    /// Set or clear a task's priority
+    pub fn set_priority(&mut self, task_id: TaskId, priority: Option<Priority>) -> Result<(), String> {
+        let (col_idx, pos) = self.task_position(task_id).ok_or("Task not found")?;
+        self.columns[col_idx].tasks[pos].priority = priority;
+        self.columns[col_idx].apply_sort();
+        Ok(())
+    }
+
+    /// The lane a task falls into under the current lane mode
+    pub fn lane_key(&self, task: &Task) -> Option<String> {
+        match self.lane_mode {
+            LaneMode::Off => None,
+            LaneMode::Lane => task.lane.clone(),
+            LaneMode::Tag => task.tags.first().cloned(),
+            LaneMode::Priority => task.priority.map(|p| p.label().to_string()),
+        }
+    }
+
+    /// All lanes on the board in display order, the empty lane last
+    pub fn lanes(&self) -> Vec<Option<String>> {
+        if self.lane_mode == LaneMode::Priority {
+            // Highest priority on top, like sorted columns
+            let mut lanes: Vec<Option<String>> = [Priority::Urgent, Priority::High, Priority::Medium, Priority::Low]
+                .iter()
+                .map(|p| Some(p.label().to_string()))
+                .collect();
+            lanes.push(None);
+            return lanes;
+        }
+
+        let mut keys: Vec<String> = self
+            .columns
+            .iter()
+            .flat_map(|c| c.tasks.iter())
+            .filter_map(|t| self.lane_key(t))
+            .collect();
+        keys.sort_by_key(|k| k.to_lowercase());
+        keys.dedup();
+
+        let mut lanes: Vec<Option<String>> = keys.into_iter().map(Some).collect();
+        lanes.push(None);
+        lanes
+    }
+
+    /// Set or clear a task's lane field
+    pub fn set_lane(&mut self, task_id: TaskId, lane: Option<String>) -> Result<(), String> {
+        self.find_task_mut(task_id)?.lane = lane;
+        Ok(())
+    }
+
+    /// Put a task into a lane under the current lane mode. Depending on the
+    /// mode this sets its lane field, replaces its first tag (or removes it
+    /// for the untagged lane, if it's the only one) or sets its priority.
+    pub fn assign_lane(&mut self, task_id: TaskId, key: Option<String>) -> Result<(), String> {
+        match self.lane_mode {
+            LaneMode::Off => Ok(()),
+            LaneMode::Lane => self.set_lane(task_id, key),
+            LaneMode::Tag => {
+                let task = self.find_task_mut(task_id)?;
+                if task.tags.first() == key.as_ref() {
+                    return Ok(());
+                }
+                match key {
+                    // The lane tag replaces the one the task was filed under
+                    Some(tag) => {
+                        if !task.tags.is_empty() {
+                            let from = task.tags.remove(0);
+                            task.record(TaskEventKind::Untagged { tag: from });
+                        }
+                        let had_tag = task.tags.contains(&tag);
+                        task.tags.retain(|t| *t != tag);
+                        task.tags.insert(0, tag.clone());
+                        if !had_tag {
+                            task.record(TaskEventKind::Tagged { tag });
+                        }
+                    }
+                    // Any other tag would put it in another lane, so only a
+                    // task with just the one can go untagged
+                    None => {
+                        if task.tags.len() > 1 {
+                            return Err(format!(
+                                "Task {} has other tags, remove them with 'tag rm' to leave it untagged",
+                                task_id
+                            ));
+                        }
+                        let from = task.tags.remove(0);
+                        task.record(TaskEventKind::Untagged { tag: from });
+                    }
+                }
+                Ok(())
+            }
+            LaneMode::Priority => {
+                let priority = key.as_deref().and_then(Priority::parse);
+                self.set_priority(task_id, priority)
+            }
+        }
+    }
+
+    /// Set or clear a task's due date
+    pub fn set_due(&mut self, task_id: TaskId, due: Option<NaiveDate>) -> Result<(), String> {
+        self.find_task_mut(task_id)?.due = due;
+        Ok(())
+    }
+
+    /// Append a step to a task's checklist, returning its index
+    pub fn add_checklist_item(&mut self, task_id: TaskId, text: String) -> Result<usize, String> {
+        let task = self.find_task_mut(task_id)?;
+        task.checklist.push(ChecklistItem { text, done: false });
+        Ok(task.checklist.len() - 1)
+    }
+
+    /// Flip a checklist step between done and not done, returning the new state
+    pub fn toggle_checklist_item(&mut self, task_id: TaskId, index: usize) -> Result<bool, String> {
+        let task = self.find_task_mut(task_id)?;
+        let item = task
+            .checklist
+            .get_mut(index)
+            .ok_or_else(|| format!("Task {} has no checklist item {}", task_id, index + 1))?;
+        item.done = !item.done;
+        Ok(item.done)
+    }
+
+    /// Remove a step from a task's checklist, returning it
+    pub fn remove_checklist_item(&mut self, task_id: TaskId, index: usize) -> Result<ChecklistItem, String> {
+        let task = self.find_task_mut(task_id)?;
+        if index >= task.checklist.len() {
+            return Err(format!("Task {} has no checklist item {}", task_id, index + 1));
+        }
+        Ok(task.checklist.remove(index))
+    }
+
+    /// Turn a column's sort-by-priority mode on or off
+    pub fn set_column_sort(&mut self, name: &str, sort_by_priority: bool) -> Result<(), String> {
+        let column = self
+            .find_column_by_name(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+        column.sort_by_priority = sort_by_priority;
+        column.apply_sort();
+        Ok(())
+    }
+
+    /// Put columns in their saved order and renumber positions.
+    ///
+    /// Older boards saved every column at position 0; the stable sort keeps
+    /// their existing order.
+    pub fn restore_column_order(&mut self) {
+        self.columns.sort_by_key(|c| c.position);
+        self.update_column_positions();
+    }
+
+    /// Boards from before columns could be marked done have no done column;
+    /// treat a column called "Done" as one
+    pub fn ensure_done_column(&mut self) {
+        if self.columns.iter().any(|c| c.done) {
+            return;
+        }
         for column in &mut self.columns {
-            if let Some(task) = column.tasks.iter_mut().find(|t| t.id == task_id) {
-                task.title = new_title;
-                return Ok(());
+            if column.name.eq_ignore_ascii_case("done") {
+                column.done = true;
             }
         }
-        Err("Task not found".to_string())
+    }
+
+    /// Mark or unmark a column as done
+    pub fn set_column_done(&mut self, name: &str, done: bool) -> Result<(), String> {
+        let column = self
+            .find_column_by_name(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+        column.done = done;
+        Ok(())
+    }
+
+    /// Whether a column holds work in progress: anything after the first
+    /// column that isn't a done column
+    pub fn is_in_progress_column(&self, col_idx: usize) -> bool {
+        col_idx > 0 && !self.columns[col_idx].done
+    }
+
+    /// Keep each column's position in sync with its place in the list
+    fn update_column_positions(&mut self) {
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            column.position = i;
+        }
+    }
+
+    /// Add a new column at the right end of the board
+    pub fn add_column(&mut self, name: &str) -> Result<ColumnId, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Column name cannot be empty".to_string());
+        }
+        if self.column_index(name).is_some() {
+            return Err(format!("Column '{}' already exists", name));
+        }
+
+        let id = self.allocate_column_id();
+        self.columns.push(Column::new(id, name));
+        self.update_column_positions();
+        Ok(id)
+    }
+
+    /// Rename a column
+    pub fn rename_column(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err("Column name cannot be empty".to_string());
+        }
+
+        let col_idx = self
+            .column_index(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+
+        if let Some(existing) = self.column_index(new_name) {
+            if existing != col_idx {
+                return Err(format!("Column '{}' already exists", new_name));
+            }
+        }
+
+        self.columns[col_idx].name = new_name.to_string();
+        Ok(())
+    }
+
+    /// Delete a column, optionally moving its tasks to another column first.
+    /// Refuses to drop tasks silently or to delete the last column.
+    pub fn delete_column(&mut self, name: &str, move_tasks_to: Option<&str>) -> Result<usize, String> {
+        let col_idx = self
+            .column_index(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+
+        if self.columns.len() == 1 {
+            return Err("Cannot delete the last column".to_string());
+        }
+
+        let task_count = self.columns[col_idx].tasks.len();
+        let target_idx = match move_tasks_to {
+            Some(target) => {
+                let target_idx = self
+                    .column_index(target)
+                    .ok_or_else(|| format!("Column '{}' not found", target))?;
+                if target_idx == col_idx {
+                    return Err("Cannot move tasks into the column being deleted".to_string());
+                }
+                Some(target_idx)
+            }
+            None if task_count > 0 => {
+                return Err(format!(
+                    "Column '{}' has {} tasks; use --move-tasks-to <column>",
+                    self.columns[col_idx].name, task_count
+                ));
+            }
+            None => None,
+        };
+
+        // Tasks go the way a move would take them, so they get a history
+        // entry, the target's sort order and its WIP limit
+        if let Some(target_idx) = target_idx {
+            let target = self.columns[target_idx].name.clone();
+            let ids: Vec<TaskId> = self.columns[col_idx].tasks.iter().map(|t| t.id).collect();
+            for id in ids {
+                self.move_task(id, &target, None)?;
+            }
+        }
+
+        self.columns.remove(col_idx);
+        self.update_column_positions();
+        Ok(task_count)
+    }
+
+    /// Move a column to a new index (0-based, clamped to the board)
+    pub fn move_column(&mut self, name: &str, index: usize) -> Result<usize, String> {
+        let col_idx = self
+            .column_index(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+
+        let column = self.columns.remove(col_idx);
+        let index = index.min(self.columns.len());
+        self.columns.insert(index, column);
+
+        self.update_column_positions();
+        Ok(index)
     }
 
     /// Set status message
     pub fn set_status(&mut self, message: String) {
         self.status_message = Some(message);
     }
+
+    /// Record a warning to show with the current change's status message
+    pub fn notify(&mut self, message: String) {
+        self.notices.push(message);
+    }
+
+    /// Set the status message, appending any pending notices
+    pub fn set_status_with_notices(&mut self, message: String) {
+        let mut lines = vec![message];
+        lines.extend(self.notices.drain(..).map(|n| format!("⚠ {}", n)));
+        self.set_status(lines.join("\n"));
+    }
 }
 
 /// A column in the kanban board
@@ -138,17 +1296,182 @@ pub struct Column {
     pub name: String,
     pub tasks: Vec<Task>,
     pub position: usize,
+    /// Keep tasks ordered by priority (highest first) instead of manual order
+    #[serde(default)]
+    pub sort_by_priority: bool,
+    /// Maximum number of tasks before the column counts as overloaded
+    #[serde(default)]
+    pub wip_limit: Option<usize>,
+    /// Tasks here are finished and no longer block anything
+    #[serde(default)]
+    pub done: bool,
 }
 
-static NEXT_COLUMN_ID: AtomicU32 = AtomicU32::new(1);
-
 impl Column {
-    pub fn new(name: &str) -> Self {
+    pub fn new(id: ColumnId, name: &str) -> Self {
         Self {
-            id: NEXT_COLUMN_ID.fetch_add(1, Ordering::SeqCst),
+            id,
             name: name.to_string(),
             tasks: Vec::new(),
             position: 0,
+            sort_by_priority: false,
+            wip_limit: None,
+            done: false,
+        }
+    }
+
+    /// Sum of the estimates in the column, if any task has one
+    pub fn total_estimate(&self) -> Option<f32> {
+        sum_estimates(&self.tasks)
+    }
+
+    /// Re-sort tasks if the column is in sort-by-priority mode.
+    /// The sort is stable, so tasks of equal priority keep their manual order.
+    fn apply_sort(&mut self) {
+        if self.sort_by_priority {
+            self.tasks.sort_by_key(|t| Reverse(t.priority));
+        }
+    }
+}
+
+/// How often a task comes back once it's done
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "every", rename_all = "lowercase")]
+pub enum Recurrence {
+    Day,
+    /// Monday to Friday
+    Weekday,
+    /// On the given days of the week
+    Week { days: Vec<Weekday> },
+    /// On a day of the month, clamped to the month's last day
+    Month { day: u32 },
+}
+
+impl Recurrence {
+    /// Parse a rule: `every day`, `every weekday`, `every mon,thu` or
+    /// `every month on 1`. The leading `every` is optional.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        const USAGE: &str = "Invalid rule (try every day, every weekday, every mon,thu or every month on 1)";
+
+        let input = input.trim().to_lowercase();
+        let rule = input.strip_prefix("every").unwrap_or(&input).trim();
+
+        match rule {
+            "day" | "daily" => return Ok(Recurrence::Day),
+            "weekday" | "weekdays" => return Ok(Recurrence::Weekday),
+            _ => {}
+        }
+
+        if let Some(day) = rule.strip_prefix("month on") {
+            let day: u32 = day.trim().parse().map_err(|_| USAGE.to_string())?;
+            if !(1..=31).contains(&day) {
+                return Err(format!("Invalid day of month: {}", day));
+            }
+            return Ok(Recurrence::Month { day });
+        }
+
+        let mut days = Vec::new();
+        for name in rule.split(',') {
+            let day = dates::parse_weekday(name.trim()).ok_or_else(|| USAGE.to_string())?;
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+        days.sort_by_key(|d| d.num_days_from_monday());
+        Ok(Recurrence::Week { days })
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Recurrence::Day => "every day".to_string(),
+            Recurrence::Weekday => "every weekday".to_string(),
+            Recurrence::Week { days } => {
+                let names: Vec<String> = days.iter().map(|d| d.to_string().to_lowercase()).collect();
+                format!("every {}", names.join(","))
+            }
+            Recurrence::Month { day } => format!("every month on {}", day),
+        }
+    }
+
+    /// The first date matching the rule strictly after `date`
+    pub fn next_after(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Recurrence::Day => date + Duration::days(1),
+            Recurrence::Weekday => (1..=3)
+                .map(|n| date + Duration::days(n))
+                .find(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+                .unwrap_or(date + Duration::days(1)),
+            Recurrence::Week { days } => (1..=7)
+                .map(|n| date + Duration::days(n))
+                .find(|d| days.contains(&d.weekday()))
+                .unwrap_or(date + Duration::weeks(1)),
+            Recurrence::Month { day } => {
+                let this_month = day_in_month(date.year(), date.month(), *day);
+                if this_month > date {
+                    this_month
+                } else if date.month() == 12 {
+                    day_in_month(date.year() + 1, 1, *day)
+                } else {
+                    day_in_month(date.year(), date.month() + 1, *day)
+                }
+            }
+        }
+    }
+}
+
+/// A day of a month, moved back to the month's last day if it doesn't have that many
+fn day_in_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .unwrap_or_default()
+}
+
+/// Sum of the estimates of some tasks, if any of them has one
+pub fn sum_estimates<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Option<f32> {
+    tasks
+        .into_iter()
+        .filter_map(|t| t.estimate)
+        .fold(None, |sum, e| Some(sum.unwrap_or(0.0) + e))
+}
+
+/// Format an estimate without a trailing `.0`
+pub fn format_estimate(estimate: f32) -> String {
+    if estimate.fract() == 0.0 {
+        format!("{}", estimate as i64)
+    } else {
+        format!("{:.1}", estimate)
+    }
+}
+
+/// Task priority, from lowest to highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    /// Parse a priority level: low/med/high/urgent, or P3..P0
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "low" | "l" | "p3" => Some(Priority::Low),
+            "medium" | "med" | "m" | "p2" => Some(Priority::Medium),
+            "high" | "h" | "p1" => Some(Priority::High),
+            "urgent" | "u" | "p0" => Some(Priority::Urgent),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Medium => "med",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
         }
     }
 }
@@ -161,16 +1484,113 @@ pub struct Task {
     pub description: Option<String>,
     pub tags: Vec<String>,
     pub created: DateTime<Utc>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// Append-only log of what happened to the task
+    #[serde(default)]
+    pub history: Vec<TaskEvent>,
+    /// Swimlane the task belongs to when lanes are grouped by lane
+    #[serde(default)]
+    pub lane: Option<String>,
+    /// Tasks that have to be done before this one can go ahead
+    #[serde(default)]
+    pub blocked_by: Vec<TaskId>,
+    /// Spawn a fresh copy when this task is done
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// Time logged with start/stop
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+    /// Size of the task, in story points or hours
+    #[serde(default)]
+    pub estimate: Option<f32>,
+}
+
+/// An entry in a task's activity history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEvent {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: TaskEventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TaskEventKind {
+    Created { column: String },
+    Moved { from: String, to: String },
+    TitleEdited { from: String, to: String },
+    Tagged { tag: String },
+    Archived,
+    Trashed,
+    Restored { column: String },
+    Blocked { by: TaskId },
+    Unblocked { by: TaskId },
+    Recurred { next: TaskId },
+    Untagged { tag: String },
+}
+
+impl TaskEventKind {
+    /// One-line description for history listings
+    pub fn describe(&self) -> String {
+        match self {
+            TaskEventKind::Created { column } => format!("created in {}", column),
+            TaskEventKind::Moved { from, to } => format!("moved {} → {}", from, to),
+            TaskEventKind::TitleEdited { from, to } => format!("renamed '{}' → '{}'", from, to),
+            TaskEventKind::Tagged { tag } => format!("tagged #{}", tag),
+            TaskEventKind::Untagged { tag } => format!("untagged #{}", tag),
+            TaskEventKind::Archived => "archived".to_string(),
+            TaskEventKind::Trashed => "moved to trash".to_string(),
+            TaskEventKind::Restored { column } => format!("restored to {}", column),
+            TaskEventKind::Blocked { by } => format!("blocked by {}", by),
+            TaskEventKind::Unblocked { by } => format!("no longer blocked by {}", by),
+            TaskEventKind::Recurred { next } => format!("done, next occurrence is {}", next),
+        }
+    }
+}
+
+/// A stretch of time spent on a task. `end` is empty while the timer runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+    /// Length of the entry, up to now if it's still running
+    pub fn duration(&self) -> Duration {
+        self.end.unwrap_or_else(Utc::now) - self.start
+    }
+}
+
+/// One step in a task's checklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
 }
 
 impl Task {
-    pub fn new(title: String, tags: Vec<String>) -> Self {
+    pub fn new(id: TaskId, title: String, tags: Vec<String>) -> Self {
         Self {
-            id: next_task_id(),
+            id,
             title,
             description: None,
             tags,
             created: Utc::now(),
+            priority: None,
+            due: None,
+            checklist: Vec::new(),
+            history: Vec::new(),
+            lane: None,
+            blocked_by: Vec::new(),
+            recurrence: None,
+            time_entries: Vec::new(),
+            estimate: None,
         }
     }
 
@@ -178,4 +1598,82 @@ impl Task {
     pub fn short_id(&self) -> String {
         self.id.to_string()
     }
+
+    /// Append an event to the task's history
+    pub fn record(&mut self, kind: TaskEventKind) {
+        self.history.push(TaskEvent { at: Utc::now(), kind });
+    }
+
+    /// Start of the running time entry, if the timer is on
+    pub fn timer_started(&self) -> Option<DateTime<Utc>> {
+        self.time_entries.iter().find(|e| e.end.is_none()).map(|e| e.start)
+    }
+
+    /// Total time logged, including a running entry
+    pub fn time_spent(&self) -> Duration {
+        self.time_entries.iter().map(|e| e.duration()).sum()
+    }
+
+    /// Close the running time entry, returning its length
+    fn stop_timer(&mut self) -> Option<Duration> {
+        let entry = self.time_entries.iter_mut().find(|e| e.end.is_none())?;
+        entry.end = Some(Utc::now());
+        Some(entry.duration())
+    }
+
+    /// Checklist progress as (done, total)
+    pub fn checklist_progress(&self) -> (usize, usize) {
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        (done, self.checklist.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parses_recurrence_rules() {
+        assert_eq!(Recurrence::parse("every day"), Ok(Recurrence::Day));
+        assert_eq!(Recurrence::parse("Weekdays"), Ok(Recurrence::Weekday));
+        assert_eq!(
+            Recurrence::parse("every thu, mon,thu"),
+            Ok(Recurrence::Week { days: vec![Weekday::Mon, Weekday::Thu] })
+        );
+        assert_eq!(Recurrence::parse("every month on 31"), Ok(Recurrence::Month { day: 31 }));
+        assert!(Recurrence::parse("every month on 32").is_err());
+        assert!(Recurrence::parse("every fortnight").is_err());
+    }
+
+    #[test]
+    fn weekly_rules_skip_to_the_next_matching_day() {
+        // 2026-10-16 is a Friday
+        assert_eq!(Recurrence::Day.next_after(date(2026, 10, 16)), date(2026, 10, 17));
+        assert_eq!(Recurrence::Weekday.next_after(date(2026, 10, 16)), date(2026, 10, 19));
+        let mon_thu = Recurrence::Week { days: vec![Weekday::Mon, Weekday::Thu] };
+        assert_eq!(mon_thu.next_after(date(2026, 10, 16)), date(2026, 10, 19));
+        assert_eq!(mon_thu.next_after(date(2026, 10, 19)), date(2026, 10, 22));
+    }
+
+    #[test]
+    fn monthly_rules_clamp_to_the_end_of_short_months() {
+        let last = Recurrence::Month { day: 31 };
+        assert_eq!(last.next_after(date(2026, 1, 31)), date(2026, 2, 28));
+        assert_eq!(last.next_after(date(2026, 2, 28)), date(2026, 3, 31));
+        assert_eq!(last.next_after(date(2026, 4, 15)), date(2026, 4, 30));
+        assert_eq!(last.next_after(date(2028, 1, 31)), date(2028, 2, 29));
+
+        let thirtieth = Recurrence::Month { day: 30 };
+        assert_eq!(thirtieth.next_after(date(2026, 2, 28)), date(2026, 3, 30));
+    }
+
+    #[test]
+    fn monthly_rules_roll_over_the_year() {
+        assert_eq!(Recurrence::Month { day: 31 }.next_after(date(2026, 12, 31)), date(2027, 1, 31));
+        assert_eq!(Recurrence::Month { day: 1 }.next_after(date(2026, 12, 1)), date(2027, 1, 1));
+    }
 }
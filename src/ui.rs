@@ -1,6 +1,9 @@
-use crate::commands::execute_command;
-use crate::state::{KanbanState, Task, TaskId};
+use crate::commands::{completions, execute_command, Completion, CompletionKind};
+use crate::dates;
+use crate::persistence;
+use crate::state::{format_estimate, KanbanState, LaneMode, Priority, Task, TaskId};
 use egui::{Color32, Frame, Margin, Rounding, Stroke, Vec2};
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 // Track if style has been initialized to avoid setting it every frame
@@ -24,7 +27,14 @@ pub fn render_ui(ctx: &egui::Context, state: &mut KanbanState) {
             ui.spacing_mut().item_spacing = Vec2::new(8.0, 8.0);
 
             // Custom title bar for window dragging (since decorations are disabled)
-            render_title_bar(ui, ctx);
+            render_title_bar(ui, ctx, &state.board_name);
+
+            // Undo/redo shortcuts. Consumed before the command bar so the
+            // text field doesn't treat them as its own undo. The task editor's
+            // fields keep their own undo while it's open.
+            if state.editing.is_none() {
+                handle_undo_shortcuts(ctx, state);
+            }
 
             // Command bar at top
             render_command_bar(ui, state);
@@ -34,7 +44,16 @@ pub fn render_ui(ctx: &egui::Context, state: &mut KanbanState) {
             // Status message
             if let Some(msg) = &state.status_message {
                 ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new(msg).color(Color32::from_rgb(100, 200, 100)));
+                    let text = egui::RichText::new(msg);
+                    let text = if msg.starts_with("Error:") {
+                        // Parse errors put a caret under the input on a
+                        // separate line, which only lines up in a monospace font
+                        let text = if msg.contains('\n') { text.monospace() } else { text };
+                        text.color(Color32::from_rgb(230, 110, 100))
+                    } else {
+                        text.color(Color32::from_rgb(100, 200, 100))
+                    };
+                    ui.label(text);
                 });
                 ui.add_space(5.0);
             }
@@ -46,9 +65,11 @@ pub fn render_ui(ctx: &egui::Context, state: &mut KanbanState) {
                     render_columns(ui, state);
                 });
         });
+
+    render_task_editor(ctx, state);
 }
 
-fn render_title_bar(ui: &mut egui::Ui, ctx: &egui::Context) {
+fn render_title_bar(ui: &mut egui::Ui, ctx: &egui::Context, board_name: &str) {
     let title_bar_height = 28.0;
 
     // Allocate space for title bar using standard egui layout
@@ -75,7 +96,7 @@ fn render_title_bar(ui: &mut egui::Ui, ctx: &egui::Context) {
     ui.painter().text(
         text_pos,
         egui::Align2::LEFT_CENTER,
-        "📋 Kanban Overlay",
+        format!("📋 Kanban Overlay — {}", board_name),
         egui::FontId::proportional(14.0),
         Color32::from_rgb(150, 150, 170),
     );
@@ -91,67 +112,374 @@ fn render_title_bar(ui: &mut egui::Ui, ctx: &egui::Context) {
     );
 }
 
+fn handle_undo_shortcuts(ctx: &egui::Context, state: &mut KanbanState) {
+    let redo_shortcut = egui::KeyboardShortcut::new(
+        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+        egui::Key::Z,
+    );
+    let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+    // Check redo first: Ctrl+Z also matches when Shift is held
+    if ctx.input_mut(|i| i.consume_shortcut(&redo_shortcut)) {
+        execute_command("redo", state);
+    } else if ctx.input_mut(|i| i.consume_shortcut(&undo_shortcut)) {
+        execute_command("undo", state);
+    }
+}
+
 fn render_command_bar(ui: &mut egui::Ui, state: &mut KanbanState) {
-    ui.horizontal(|ui| {
-        ui.label(egui::RichText::new(">").color(Color32::from_rgb(100, 150, 255)).size(18.0));
+    let input_id = egui::Id::new("command_input");
+    let focused = state.editing.is_none() && ui.memory(|m| m.has_focus(input_id));
 
+    // Up/Down and Ctrl+R go through the command history. Consumed before the
+    // text field sees them, like Tab below.
+    let mut replaced = focused && handle_history_keys(ui, state);
+    let searching = state.command_history.search().is_some();
+
+    // Tab completes the word being typed and Tab again cycles through the
+    // candidates, Shift+Tab backwards. Consumed before the text field sees
+    // it, which would otherwise move focus away.
+    if focused && !searching {
+        let backwards = ui.input_mut(|i| i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab));
+        if backwards || ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+            replaced |= complete_command(ui.ctx(), state, backwards);
+        }
+    }
+
+    let input_rect = ui.horizontal(|ui| {
+        let prompt = if searching { "🔍" } else { ">" };
+        ui.label(egui::RichText::new(prompt).color(Color32::from_rgb(100, 150, 255)).size(18.0));
+
+        // While searching the field edits the search query instead
+        let mut query = state.command_history.search().map(|s| s.query.clone()).unwrap_or_default();
+        let (text, hint) = if searching {
+            (&mut query, "Search history...")
+        } else {
+            (&mut state.command_input, "Type a command... (try 'help', Tab completes)")
+        };
         let response = ui.add(
-            egui::TextEdit::singleline(&mut state.command_input)
+            egui::TextEdit::singleline(text)
+                .id(input_id)
                 .desired_width(ui.available_width() - 80.0)
-                .hint_text("Type a command... (try 'help')")
+                .hint_text(hint)
                 .frame(true),
         );
+        if searching && response.changed() {
+            state.command_history.set_query(query);
+        }
 
         // Only request focus if the input doesn't already have it
-        // This prevents calling request_focus() every frame which causes continuous repaints
-        if !response.has_focus() && state.command_input.is_empty() {
+        // This prevents calling request_focus() every frame which causes continuous repaints.
+        // Leave focus alone while the task editor is open.
+        let editor_open = state.editing.is_some();
+        if !response.has_focus() && state.command_input.is_empty() && !editor_open {
             response.request_focus();
         }
 
         // Execute on Enter - check if Enter was pressed while focused
-        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-            let command = state.command_input.clone();
+        if !editor_open && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let command = if searching {
+                let found = state.command_history.search_match().map(str::to_string);
+                state.command_history.end_search();
+                found.unwrap_or_default()
+            } else {
+                state.command_input.clone()
+            };
             if !command.trim().is_empty() {
                 execute_command(&command, state);
+                if state.command_history.push(&command) {
+                    if let Err(e) = persistence::append_history(&command) {
+                        eprintln!("Failed to save command history: {}", e);
+                    }
+                }
                 state.command_input.clear();
             }
         }
 
         // Clear button
         if ui.button("✕").clicked() {
+            state.command_history.end_search();
             state.command_input.clear();
         }
-    });
+
+        response.rect
+    })
+    .inner;
+
+    if let Some(search) = state.command_history.search() {
+        render_history_search(ui, state, search.query.is_empty());
+        if replaced {
+            let query = search.query.clone();
+            move_cursor_to_end(ui.ctx(), input_id, &query);
+        }
+        return;
+    }
+
+    // Typing anything else ends the cycle
+    if CompletionCycle::load(ui.ctx()).is_some_and(|cycle| cycle.input() != state.command_input) {
+        CompletionCycle::clear(ui.ctx());
+    }
+
+    if render_completions(ui, state, input_rect, replaced) {
+        ui.memory_mut(|m| m.request_focus(input_id));
+        replaced = true;
+    }
+
+    // The text changed under the cursor, put it back at the end
+    if replaced {
+        move_cursor_to_end(ui.ctx(), input_id, &state.command_input);
+    }
 }
 
-fn render_columns(ui: &mut egui::Ui, state: &mut KanbanState) {
-    ui.horizontal_top(|ui| {
-        let column_width = 300.0;
-        let num_columns = state.columns.len();
+/// Up/Down recall earlier commands, Ctrl+R searches back through them.
+/// Returns whether the command bar text was replaced.
+fn handle_history_keys(ui: &mut egui::Ui, state: &mut KanbanState) -> bool {
+    let history = &mut state.command_history;
 
-        for col_idx in 0..num_columns {
-            render_column(ui, state, col_idx, column_width);
-            if col_idx < num_columns - 1 {
-                ui.add_space(15.0);
+    if ui.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::R)) {
+        if history.search().is_some() {
+            history.search_older();
+        } else {
+            history.start_search();
+            CompletionCycle::clear(ui.ctx());
+        }
+        return true;
+    }
+
+    if history.search().is_some() {
+        // Escape only cancels the search, the overlay stays open
+        if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)) {
+            history.end_search();
+            return true;
+        }
+        // Tab takes the match into the command bar to edit before running
+        if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+            if let Some(found) = history.search_match() {
+                state.command_input = found.to_string();
+            }
+            history.end_search();
+            return true;
+        }
+        return false;
+    }
+
+    let recalled = if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
+        history.older(&state.command_input)
+    } else if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+        history.newer()
+    } else {
+        None
+    };
+    match recalled {
+        Some(command) => {
+            state.command_input = command.to_string();
+            CompletionCycle::clear(ui.ctx());
+            true
+        }
+        None => false,
+    }
+}
+
+/// The command a history search has found, below the command bar
+fn render_history_search(ui: &mut egui::Ui, state: &KanbanState, empty_query: bool) {
+    ui.horizontal(|ui| {
+        match state.command_history.search_match() {
+            Some(found) => {
+                ui.label(egui::RichText::new(found).monospace().color(Color32::WHITE));
+            }
+            None if empty_query => {}
+            None => {
+                ui.label(egui::RichText::new("No match").color(Color32::GRAY));
             }
         }
+        ui.label(
+            egui::RichText::new("Enter runs · Tab edits · Ctrl+R older · Esc cancels")
+                .small()
+                .color(Color32::GRAY),
+        );
     });
 }
 
-fn render_column(ui: &mut egui::Ui, state: &mut KanbanState, col_idx: usize, width: f32) {
-    ui.vertical(|ui| {
-        ui.set_width(width);
+/// Candidates for the word being completed, with one of them filled in
+#[derive(Debug, Clone)]
+struct CompletionCycle {
+    /// The command bar text before the word
+    prefix: String,
+    candidates: Vec<Completion>,
+    selected: usize,
+}
 
-        // Column header
-        // Need to clone column_name since we mutate state later
-        let column_name = state.columns[col_idx].name.clone();
-        let task_count = state.columns[col_idx].tasks.len();
+impl CompletionCycle {
+    /// Kept in egui's memory between frames, it's only command bar state
+    fn id() -> egui::Id {
+        egui::Id::new("command_completion_cycle")
+    }
 
-        ui.horizontal(|ui| {
-            ui.heading(egui::RichText::new(&column_name).size(20.0));
-            ui.label(egui::RichText::new(format!("({})", task_count)).color(Color32::GRAY));
+    fn load(ctx: &egui::Context) -> Option<Self> {
+        ctx.data(|d| d.get_temp(Self::id()))
+    }
+
+    fn store(self, ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(Self::id(), self));
+    }
+
+    fn clear(ctx: &egui::Context) {
+        ctx.data_mut(|d| d.remove::<Self>(Self::id()));
+    }
+
+    /// The command bar text with the selected candidate filled in
+    fn input(&self) -> String {
+        format!("{}{}", self.prefix, self.candidates[self.selected].text)
+    }
+
+    /// Select the next candidate, or the previous one, wrapping around
+    fn step(&mut self, backwards: bool) {
+        let count = self.candidates.len();
+        self.selected = if backwards {
+            (self.selected + count - 1) % count
+        } else {
+            (self.selected + 1) % count
+        };
+    }
+}
+
+/// Complete the word being typed, or move on to the next candidate if
+/// Tab was just pressed. Returns whether the command bar text changed.
+fn complete_command(ctx: &egui::Context, state: &mut KanbanState, backwards: bool) -> bool {
+    if let Some(mut cycle) = CompletionCycle::load(ctx).filter(|cycle| cycle.input() == state.command_input) {
+        cycle.step(backwards);
+        state.command_input = cycle.input();
+        cycle.store(ctx);
+        return true;
+    }
+
+    let completions = completions(&state.command_input, state);
+    let prefix = state.command_input[..completions.start].to_string();
+    let mut candidates = completions.candidates;
+    match candidates.len() {
+        0 => false,
+        // The only candidate is taken, ready for the next word
+        1 => {
+            state.command_input = format!("{}{} ", prefix, candidates.remove(0).text);
+            true
+        }
+        count => {
+            let selected = if backwards { count - 1 } else { 0 };
+            let cycle = CompletionCycle { prefix, candidates, selected };
+            state.command_input = cycle.input();
+            cycle.store(ctx);
+            true
+        }
+    }
+}
+
+/// Popup under the command bar listing the candidates being cycled through,
+/// or the tags matching a `#tag` as it's typed. Returns whether one was
+/// clicked and filled in.
+fn render_completions(ui: &mut egui::Ui, state: &mut KanbanState, input_rect: egui::Rect, stepped: bool) -> bool {
+    let (prefix, candidates, selected) = match CompletionCycle::load(ui.ctx()) {
+        Some(cycle) => (cycle.prefix, cycle.candidates, Some(cycle.selected)),
+        None => {
+            let word = state.command_input.rsplit(char::is_whitespace).next().unwrap_or("");
+            if !word.starts_with('#') {
+                return false;
+            }
+            let completions = completions(&state.command_input, state);
+            let prefix = state.command_input[..completions.start].to_string();
+            (prefix, completions.candidates, None)
+        }
+    };
+    if candidates.is_empty() {
+        return false;
+    }
+
+    let mut picked = None;
+    egui::Area::new(egui::Id::new("command_completions"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(input_rect.left_bottom() + Vec2::new(0.0, 4.0))
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_min_width(input_rect.width());
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    for (i, candidate) in candidates.iter().enumerate() {
+                        let color = match candidate.kind {
+                            CompletionKind::Tag => {
+                                let tag = candidate.text.trim_start_matches('#');
+                                match state.tag_colors.get(tag) {
+                                    Some([r, g, b]) => Color32::from_rgb(*r, *g, *b),
+                                    None => Color32::from_rgb(100, 150, 255),
+                                }
+                            }
+                            _ => Color32::LIGHT_GRAY,
+                        };
+                        ui.horizontal(|ui| {
+                            let is_selected = selected == Some(i);
+                            let text = egui::RichText::new(&candidate.text).monospace().color(color);
+                            let response = ui.selectable_label(is_selected, text);
+                            if let Some(detail) = &candidate.detail {
+                                ui.label(egui::RichText::new(detail).size(11.0).color(Color32::GRAY));
+                            }
+                            if is_selected && stepped {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                picked = Some(i);
+                            }
+                        });
+                    }
+                });
+                let hint = if selected.is_some() { "Tab ↹ next · Shift+Tab previous" } else { "Tab ↹ complete" };
+                ui.label(egui::RichText::new(hint).size(10.0).color(Color32::GRAY));
+            });
         });
 
+    let Some(i) = picked else {
+        return false;
+    };
+    state.command_input = format!("{}{} ", prefix, candidates[i].text);
+    CompletionCycle::clear(ui.ctx());
+    true
+}
+
+/// Place the text cursor of a text field after its last character
+fn move_cursor_to_end(ctx: &egui::Context, id: egui::Id, text: &str) {
+    if let Some(mut text_state) = egui::TextEdit::load_state(ctx, id) {
+        let end = egui::text::CCursor::new(text.chars().count());
+        text_state
+            .cursor
+            .set_char_range(Some(egui::text::CCursorRange::one(end)));
+        text_state.store(ctx, id);
+    }
+}
+
+fn render_columns(ui: &mut egui::Ui, state: &mut KanbanState) {
+    let column_width = 300.0;
+
+    if state.lane_mode == LaneMode::Off {
+        ui.horizontal_top(|ui| {
+            let num_columns = state.columns.len();
+
+            for col_idx in 0..num_columns {
+                render_column(ui, state, col_idx, column_width);
+                if col_idx < num_columns - 1 {
+                    ui.add_space(15.0);
+                }
+            }
+        });
+    } else {
+        render_lanes(ui, state, column_width);
+    }
+
+    // A drag released outside every column is cancelled
+    if ui.input(|i| i.pointer.any_released()) {
+        state.dragging = None;
+    }
+}
+
+fn render_column(ui: &mut egui::Ui, state: &mut KanbanState, col_idx: usize, width: f32) {
+    ui.vertical(|ui| {
+        ui.set_width(width);
+        render_column_header(ui, state, col_idx);
         ui.separator();
 
         // Tasks
@@ -159,120 +487,492 @@ fn render_column(ui: &mut egui::Ui, state: &mut KanbanState, col_idx: usize, wid
             .max_height(600.0)
             .auto_shrink([false, false])
             .show(ui, |ui| {
-                let mut task_to_delete: Option<TaskId> = None;
+                let cards = render_task_list(ui, state, col_idx, None, width);
+                let area = ui.clip_rect();
+                handle_drop(ui, state, col_idx, None, area, &cards);
+            });
+    });
+}
 
-                // Clone tasks to avoid borrow checker issues during mutation
-                // This is still more efficient than the original since we only clone when rendering
-                let tasks = state.columns[col_idx].tasks.clone();
+/// Swimlanes: a row of column headers, then one collapsible row of cells per lane
+fn render_lanes(ui: &mut egui::Ui, state: &mut KanbanState, width: f32) {
+    let num_columns = state.columns.len();
 
-                for task in tasks.iter() {
-                    let response = render_task_card(ui, task, &column_name);
+    ui.horizontal_top(|ui| {
+        for col_idx in 0..num_columns {
+            ui.vertical(|ui| {
+                ui.set_width(width);
+                render_column_header(ui, state, col_idx);
+            });
+            if col_idx < num_columns - 1 {
+                ui.add_space(15.0);
+            }
+        }
+    });
+    ui.separator();
 
-                    // Handle drag start
-                    if response.drag_started() {
-                        state.dragging = Some(task.id);
+    egui::ScrollArea::vertical()
+        .max_height(600.0)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for lane in state.lanes() {
+                let count = state
+                    .columns
+                    .iter()
+                    .flat_map(|c| c.tasks.iter())
+                    .filter(|t| state.lane_key(t) == lane)
+                    .count();
+                // Keep the lanes a task can be dragged into visible, hide
+                // other empty ones
+                if count == 0 && state.dragging.is_none() {
+                    continue;
+                }
+
+                // Lane header, click to fold the lane away
+                let collapsed = state.collapsed_lanes.contains(&lane);
+                let label = lane.clone().unwrap_or_else(|| state.lane_mode.empty_lane_label().to_string());
+                let arrow = if collapsed { "▶" } else { "▼" };
+                let header = ui.selectable_label(
+                    false,
+                    egui::RichText::new(format!("{} {} ({})", arrow, label, count))
+                        .size(13.0)
+                        .strong()
+                        .color(Color32::from_rgb(150, 150, 170)),
+                );
+                if header.clicked() {
+                    if collapsed {
+                        state.collapsed_lanes.remove(&lane);
+                    } else {
+                        state.collapsed_lanes.insert(lane.clone());
                     }
+                }
+                if collapsed {
+                    continue;
+                }
 
-                    // Handle drop
-                    if let Some(dragging_id) = state.dragging {
-                        if response.hovered() && ui.input(|i| i.pointer.any_released()) {
-                            // Move task to this column
-                            let _ = state.move_task(dragging_id, &column_name);
-                            state.dragging = None;
+                // One cell per column. Drops are handled once the row is laid
+                // out, so every cell's drop area spans the full row height.
+                let row = ui.horizontal_top(|ui| {
+                    let mut cells = Vec::with_capacity(num_columns);
+                    for col_idx in 0..num_columns {
+                        let cell = ui.vertical(|ui| {
+                            ui.set_width(width);
+                            render_task_list(ui, state, col_idx, Some(&lane), width)
+                        });
+                        cells.push((cell.inner, cell.response.rect.x_range()));
+                        if col_idx < num_columns - 1 {
+                            ui.add_space(15.0);
                         }
                     }
+                    cells
+                });
 
-                    // Context menu (right-click)
-                    response.context_menu(|ui| {
-                        if ui.button("Delete").clicked() {
-                            task_to_delete = Some(task.id);
-                            ui.close_menu();
-                        }
-                        if ui.button("Copy ID").clicked() {
-                            ui.output_mut(|o| o.copied_text = task.short_id());
-                            ui.close_menu();
-                        }
-                    });
+                for (col_idx, (cards, x_range)) in row.inner.iter().enumerate() {
+                    let area = egui::Rect::from_x_y_ranges(*x_range, row.response.rect.y_range())
+                        .intersect(ui.clip_rect());
+                    handle_drop(ui, state, col_idx, Some(&lane), area, cards);
                 }
 
-                // Delete task if requested
-                if let Some(id) = task_to_delete {
-                    let _ = state.delete_task(id);
-                }
+                ui.add_space(6.0);
+            }
+        });
+}
 
-                // Drop zone for empty columns
-                if tasks.is_empty() {
-                    if let Some(dragging_id) = state.dragging {
-                        let drop_zone = ui.allocate_response(
-                            Vec2::new(width - 20.0, 50.0),
-                            egui::Sense::hover(),
-                        );
+fn render_column_header(ui: &mut egui::Ui, state: &KanbanState, col_idx: usize) {
+    let column = &state.columns[col_idx];
+    let task_count = column.tasks.len();
 
-                        Frame::none()
-                            .fill(Color32::from_rgba_premultiplied(50, 50, 60, 100))
-                            .rounding(Rounding::same(5.0))
-                            .stroke(Stroke::new(2.0, Color32::from_rgb(80, 80, 100)))
-                            .show(ui, |ui| {
-                                ui.centered_and_justified(|ui| {
-                                    ui.label("Drop here");
-                                });
-                            });
-
-                        if drop_zone.hovered() && ui.input(|i| i.pointer.any_released()) {
-                            let _ = state.move_task(dragging_id, &column_name);
-                            state.dragging = None;
-                        }
-                    }
+    ui.horizontal(|ui| {
+        ui.heading(egui::RichText::new(&column.name).size(20.0));
+        match column.wip_limit {
+            Some(limit) => {
+                // Count against the WIP limit, red once it's exceeded
+                let color = if task_count > limit {
+                    Color32::from_rgb(235, 80, 80)
+                } else {
+                    Color32::GRAY
+                };
+                ui.label(egui::RichText::new(format!("({}/{})", task_count, limit)).color(color));
+            }
+            None => {
+                ui.label(egui::RichText::new(format!("({})", task_count)).color(Color32::GRAY));
+            }
+        }
+        if let Some(estimate) = column.total_estimate() {
+            ui.label(
+                egui::RichText::new(format!("Σ {}", format_estimate(estimate)))
+                    .color(Color32::from_rgb(150, 150, 170)),
+            );
+        }
+        if column.sort_by_priority {
+            ui.label(egui::RichText::new("⬇ priority").size(10.0).color(Color32::GRAY));
+        }
+        if column.done {
+            ui.label(egui::RichText::new("✔").size(12.0).color(Color32::from_rgb(100, 200, 100)));
+        }
+    });
+}
+
+/// Render the cards of a column, or only those in one lane, returning each
+/// card's task ID and rect for drag-and-drop
+fn render_task_list(
+    ui: &mut egui::Ui,
+    state: &mut KanbanState,
+    col_idx: usize,
+    lane: Option<&Option<String>>,
+    width: f32,
+) -> Vec<(TaskId, egui::Rect)> {
+    let mut task_to_delete: Option<TaskId> = None;
+    let mut task_to_archive: Option<TaskId> = None;
+
+    // Clone tasks to avoid borrow checker issues during mutation
+    // This is still more efficient than the original since we only clone when rendering
+    let tasks: Vec<Task> = state.columns[col_idx]
+        .tasks
+        .iter()
+        .filter(|t| lane.is_none_or(|lane| state.lane_key(t) == *lane))
+        .cloned()
+        .collect();
+
+    // Card rects, used to work out where a dragged task would land
+    let mut cards = Vec::with_capacity(tasks.len());
+
+    for task in tasks.iter() {
+        let blockers = state.open_blockers(task);
+        let (response, action) = render_task_card(ui, task, &blockers, &state.tag_colors);
+        cards.push((task.id, response.rect));
+
+        match action {
+            Some(CardAction::ToggleChecklistItem(index)) => {
+                let before = state.snapshot();
+                if state.toggle_checklist_item(task.id, index).is_ok() {
+                    state.record_undo(
+                        before,
+                        &format!("Toggled checklist item {} of task {}", index + 1, task.id),
+                    );
                 }
+            }
+            None => {}
+        }
+
+        // Double-click opens the detail editor
+        if response.double_clicked() {
+            let _ = state.open_editor(task.id);
+        }
+
+        // Handle drag start
+        if response.drag_started() {
+            state.dragging = Some(task.id);
+        }
+
+        // Context menu (right-click)
+        response.context_menu(|ui| {
+            if ui.button("Archive").clicked() {
+                task_to_archive = Some(task.id);
+                ui.close_menu();
+            }
+            if ui.button("Delete").clicked() {
+                task_to_delete = Some(task.id);
+                ui.close_menu();
+            }
+            if ui.button("Copy ID").clicked() {
+                ui.output_mut(|o| o.copied_text = task.short_id());
+                ui.close_menu();
+            }
+        });
+    }
+
+    // Delete (to trash) or archive task if requested
+    if let Some(id) = task_to_delete {
+        let before = state.snapshot();
+        if state.delete_task(id).is_ok() {
+            state.record_undo(before, &format!("Moved task {} to trash", id));
+        }
+    }
+    if let Some(id) = task_to_archive {
+        let before = state.snapshot();
+        if state.archive_task(id).is_ok() {
+            state.record_undo(before, &format!("Archived task {}", id));
+        }
+    }
+
+    // Drop zone for empty columns
+    if state.dragging.is_some() && tasks.is_empty() {
+        Frame::none()
+            .fill(Color32::from_rgba_premultiplied(50, 50, 60, 100))
+            .rounding(Rounding::same(5.0))
+            .stroke(Stroke::new(2.0, Color32::from_rgb(80, 80, 100)))
+            .show(ui, |ui| {
+                ui.set_min_size(Vec2::new(width - 20.0, 50.0));
+                ui.centered_and_justified(|ui| {
+                    ui.label("Drop here");
+                });
             });
+    }
+
+    cards
+}
+
+/// Show where a dragged task would land among the cards in `area`, and
+/// drop it there when released
+fn handle_drop(
+    ui: &mut egui::Ui,
+    state: &mut KanbanState,
+    col_idx: usize,
+    lane: Option<&Option<String>>,
+    area: egui::Rect,
+    cards: &[(TaskId, egui::Rect)],
+) {
+    let Some(dragging_id) = state.dragging else {
+        return;
+    };
+
+    // Use the raw pointer position rather than hovered(), which
+    // other widgets don't report while something is being dragged
+    let pointer = ui.input(|i| i.pointer.hover_pos());
+    let Some(pointer) = pointer.filter(|p| area.contains(*p)) else {
+        return;
+    };
+
+    // Insertion gap: number of cards above the pointer
+    let gap = cards.iter().filter(|(_, r)| r.center().y < pointer.y).count();
+
+    // Sorted columns decide the position themselves, so no insertion line
+    if let Some((_, first)) = cards.first().filter(|_| !state.columns[col_idx].sort_by_priority) {
+        let half_spacing = ui.spacing().item_spacing.y / 2.0;
+        let y = if gap == 0 {
+            first.top() - half_spacing
+        } else {
+            cards[gap - 1].1.bottom() + half_spacing
+        };
+        ui.painter().hline(
+            first.x_range(),
+            y,
+            Stroke::new(2.0, Color32::from_rgb(100, 150, 255)),
+        );
+    }
+
+    if ui.input(|i| i.pointer.any_released()) {
+        // In a lane only some of the column's cards are shown, so the gap is
+        // turned into a position in the whole column
+        let column = &state.columns[col_idx];
+        let position_of = |id: TaskId| column.tasks.iter().position(|t| t.id == id);
+        let index = match cards.get(gap) {
+            Some((id, _)) => position_of(*id),
+            None => cards.last().and_then(|(id, _)| position_of(*id)).map(|pos| pos + 1),
+        }
+        .unwrap_or(column.tasks.len());
+
+        drop_task(state, dragging_id, col_idx, index, lane.cloned());
+    }
+}
+
+/// Drop a dragged task into a column at an index, and into a lane if the
+/// board has swimlanes
+fn drop_task(state: &mut KanbanState, task_id: TaskId, col_idx: usize, index: usize, lane: Option<Option<String>>) {
+    // The index counts the dragged card itself if it sits above it in
+    // the same column, but the move index doesn't
+    let mut index = index;
+    if let Some((source_idx, pos)) = state.task_position(task_id) {
+        if source_idx == col_idx && pos < index {
+            index -= 1;
+        }
+    }
+
+    let column_name = state.columns[col_idx].name.clone();
+    let before = state.snapshot();
+    let result = state.move_task(task_id, &column_name, Some(index)).and_then(|()| match lane {
+        Some(lane) => state.assign_lane(task_id, lane),
+        None => Ok(()),
     });
+    match result {
+        Ok(()) => {
+            let message = format!("Moved task {} to {}", task_id, column_name);
+            state.record_undo(before, &message);
+            // Only worth a status line if the move raised a warning
+            if !state.notices.is_empty() {
+                state.set_status_with_notices(message);
+            }
+        }
+        Err(err) => {
+            // A lane that refuses the card sends it back to its column too
+            state.roll_back(&before);
+            state.notices.clear();
+            state.set_status(format!("Error: {}", err));
+        }
+    }
+    state.dragging = None;
+}
+
+/// Something the user did inside a card that changes the task
+enum CardAction {
+    ToggleChecklistItem(usize),
 }
 
-fn render_task_card(ui: &mut egui::Ui, task: &Task, _column_name: &str) -> egui::Response {
+fn render_task_card(
+    ui: &mut egui::Ui,
+    task: &Task,
+    open_blockers: &[TaskId],
+    tag_colors: &BTreeMap<String, [u8; 3]>,
+) -> (egui::Response, Option<CardAction>) {
+    // High and urgent tasks get a colored border so they stand out
+    let stroke = match task.priority {
+        Some(p @ (Priority::High | Priority::Urgent)) => Stroke::new(1.5, priority_color(p)),
+        _ => Stroke::new(1.0, Color32::from_rgb(60, 62, 74)),
+    };
+
     let frame = Frame::none()
         .fill(Color32::from_rgb(40, 42, 54))
         .rounding(Rounding::same(8.0))
-        .stroke(Stroke::new(1.0, Color32::from_rgb(60, 62, 74)))
+        .stroke(stroke)
         .inner_margin(Margin::same(10.0));
 
-    let response = frame
-        .show(ui, |ui| {
-            ui.set_min_width(280.0);
+    let mut action = None;
 
-            // Task ID (short)
-            ui.horizontal(|ui| {
-                ui.label(
-                    egui::RichText::new(&task.short_id())
-                        .size(10.0)
-                        .color(Color32::GRAY),
-                );
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    // Tags
-                    for tag in &task.tags {
+    // The card's drag/click area is the background of a scope around the
+    // frame. A Ui registers its background before its contents, so widgets
+    // inside the card (checkboxes) stay clickable on top of it.
+    let response = ui
+        .scope(|ui| {
+            // Labels would otherwise grab drags for text selection
+            ui.style_mut().interaction.selectable_labels = false;
+
+            frame.show(ui, |ui| {
+                ui.set_min_width(280.0);
+
+                // Task ID (short)
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(task.short_id())
+                            .size(10.0)
+                            .color(Color32::GRAY),
+                    );
+
+                    // Checklist progress
+                    let (done, total) = task.checklist_progress();
+                    if total > 0 {
+                        let color = if done == total {
+                            Color32::from_rgb(100, 200, 100)
+                        } else {
+                            Color32::GRAY
+                        };
+                        ui.label(
+                            egui::RichText::new(format!("☑ {}/{}", done, total))
+                                .size(10.0)
+                                .color(color),
+                        );
+                    }
+
+                    // Priority marker
+                    if let Some(priority) = task.priority {
+                        ui.label(
+                            egui::RichText::new(format!("● {}", priority.label()))
+                                .size(10.0)
+                                .strong()
+                                .color(priority_color(priority)),
+                        );
+                    }
+
+                    // Estimate
+                    if let Some(estimate) = task.estimate {
+                        ui.label(
+                            egui::RichText::new(format!("~{}", format_estimate(estimate)))
+                                .size(10.0)
+                                .color(Color32::from_rgb(150, 150, 170)),
+                        );
+                    }
+
+                    // Running timer ticks every second, otherwise the time logged so far
+                    if let Some(started) = task.timer_started() {
+                        let elapsed = chrono::Utc::now() - started;
+                        ui.label(
+                            egui::RichText::new(format!("⏱ {}", dates::format_clock(elapsed)))
+                                .size(10.0)
+                                .strong()
+                                .color(Color32::from_rgb(100, 200, 100)),
+                        );
+                        ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+                    } else if !task.time_entries.is_empty() {
                         ui.label(
-                            egui::RichText::new(format!("#{}", tag))
+                            egui::RichText::new(format!("⏱ {}", dates::format_duration(task.time_spent())))
                                 .size(10.0)
-                                .color(Color32::from_rgb(100, 150, 255)),
+                                .color(Color32::GRAY),
                         );
                     }
+
+                    // Recurrence icon, with the rule on hover
+                    if let Some(rule) = &task.recurrence {
+                        ui.label(egui::RichText::new("🔁").size(10.0).color(Color32::GRAY))
+                            .on_hover_text(rule.label());
+                    }
+
+                    // Blocked badge while any blocker is unfinished
+                    if !open_blockers.is_empty() {
+                        let ids: Vec<String> = open_blockers.iter().map(|id| id.to_string()).collect();
+                        ui.label(
+                            egui::RichText::new(format!("⛔ {}", ids.join(",")))
+                                .size(10.0)
+                                .strong()
+                                .color(Color32::from_rgb(235, 80, 80)),
+                        )
+                        .on_hover_text(format!("Blocked by {}", ids.join(", ")));
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        // Tags
+                        for tag in &task.tags {
+                            let color = match tag_colors.get(tag) {
+                                Some([r, g, b]) => Color32::from_rgb(*r, *g, *b),
+                                None => Color32::from_rgb(100, 150, 255),
+                            };
+                            ui.label(egui::RichText::new(format!("#{}", tag)).size(10.0).color(color));
+                        }
+                    });
                 });
-            });
 
-            // Task title
-            ui.label(egui::RichText::new(&task.title).size(14.0));
+                // Task title
+                ui.label(egui::RichText::new(&task.title).size(14.0));
 
-            // Description if exists
-            if let Some(desc) = &task.description {
-                ui.add_space(5.0);
-                ui.label(
-                    egui::RichText::new(desc)
-                        .size(11.0)
-                        .color(Color32::LIGHT_GRAY),
-                );
-            }
+                // Due date, colored when overdue or due today
+                if let Some(due) = task.due {
+                    let today = dates::today();
+                    let (text, color) = if due < today {
+                        (format!("📅 overdue · {}", due.format("%b %d")), Color32::from_rgb(235, 80, 80))
+                    } else if due == today {
+                        ("📅 due today".to_string(), Color32::from_rgb(240, 170, 60))
+                    } else {
+                        (format!("📅 {}", due.format("%b %d")), Color32::GRAY)
+                    };
+                    ui.label(egui::RichText::new(text).size(10.0).color(color));
+                }
+
+                // Description if exists
+                if let Some(desc) = &task.description {
+                    ui.add_space(5.0);
+                    ui.label(
+                        egui::RichText::new(desc)
+                            .size(11.0)
+                            .color(Color32::LIGHT_GRAY),
+                    );
+                }
+
+                // Checklist, with checkboxes that can be ticked directly
+                if !task.checklist.is_empty() {
+                    ui.add_space(4.0);
+                    for (i, item) in task.checklist.iter().enumerate() {
+                        let mut done = item.done;
+                        let text = egui::RichText::new(&item.text).size(11.0);
+                        let text = if item.done { text.strikethrough().color(Color32::GRAY) } else { text };
+                        if ui.checkbox(&mut done, text).changed() {
+                            action = Some(CardAction::ToggleChecklistItem(i));
+                        }
+                    }
+                }
+            });
+
+            ui.interact_bg(egui::Sense::click_and_drag())
         })
-        .response
-        .interact(egui::Sense::click_and_drag());
+        .inner;
 
     // Set cursor WITHOUT triggering repaints - only change cursor if hovered
     // Don't call on_hover_cursor as it causes repaints
@@ -280,5 +980,166 @@ fn render_task_card(ui: &mut egui::Ui, task: &Task, _column_name: &str) -> egui:
         ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
     }
 
-    response
+    // Recent history as a tooltip
+    let response = if task.history.is_empty() {
+        response
+    } else {
+        response.on_hover_ui(|ui| {
+            const MAX_EVENTS: usize = 8;
+            let skip = task.history.len().saturating_sub(MAX_EVENTS);
+            for event in &task.history[skip..] {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{}  {}",
+                        event.at.with_timezone(&chrono::Local).format("%b %d %H:%M"),
+                        event.kind.describe()
+                    ))
+                    .size(11.0),
+                );
+            }
+        })
+    };
+
+    (response, action)
+}
+
+/// Detail editor for the task being edited. Edits stay in a draft until
+/// saved, then go through `KanbanState` as one undoable change.
+fn render_task_editor(ctx: &egui::Context, state: &mut KanbanState) {
+    let Some(mut draft) = state.editing.take() else {
+        return;
+    };
+    // The task may have been deleted or archived in the meantime
+    let Some((_, task)) = state.find_task(draft.task_id) else {
+        return;
+    };
+    let task = task.clone();
+    let column_names: Vec<String> = state.columns.iter().map(|c| c.name.clone()).collect();
+
+    let mut open = true;
+    let mut save = false;
+    let mut cancel = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape));
+
+    egui::Window::new(format!("Task {}", task.id))
+        .id(egui::Id::new("task_editor"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("task_editor_fields")
+                .num_columns(2)
+                .spacing([10.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Title");
+                    ui.add(egui::TextEdit::singleline(&mut draft.title).desired_width(f32::INFINITY));
+                    ui.end_row();
+
+                    ui.label("Column");
+                    egui::ComboBox::from_id_source("task_editor_column")
+                        .selected_text(&draft.column)
+                        .show_ui(ui, |ui| {
+                            for name in &column_names {
+                                ui.selectable_value(&mut draft.column, name.clone(), name);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Tags");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.tags)
+                            .hint_text("#tag #another")
+                            .desired_width(f32::INFINITY),
+                    );
+                    ui.end_row();
+                });
+
+            ui.label("Description");
+            ui.add(
+                egui::TextEdit::multiline(&mut draft.description)
+                    .desired_rows(6)
+                    .desired_width(f32::INFINITY),
+            );
+
+            ui.separator();
+
+            // Read-only details, set through their own commands
+            let mut details = vec![format!(
+                "Created {}",
+                task.created.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            )];
+            if let Some(priority) = task.priority {
+                details.push(format!("Priority {}", priority.label()));
+            }
+            if let Some(due) = task.due {
+                details.push(format!("Due {}", due));
+            }
+            if let Some(estimate) = task.estimate {
+                details.push(format!("Estimate {}", format_estimate(estimate)));
+            }
+            if !task.time_entries.is_empty() {
+                details.push(format!("Time logged {}", dates::format_duration(task.time_spent())));
+            }
+            let (done, total) = task.checklist_progress();
+            if total > 0 {
+                details.push(format!("Checklist {}/{}", done, total));
+            }
+            if let Some(lane) = &task.lane {
+                details.push(format!("Lane {}", lane));
+            }
+            if let Some(rule) = &task.recurrence {
+                details.push(format!("Repeats {}", rule.label()));
+            }
+            if !task.blocked_by.is_empty() {
+                let ids: Vec<String> = task.blocked_by.iter().map(|id| id.to_string()).collect();
+                details.push(format!("Blocked by {}", ids.join(", ")));
+            }
+            for detail in details {
+                ui.label(egui::RichText::new(detail).size(11.0).color(Color32::GRAY));
+            }
+
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    save = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+                ui.label(egui::RichText::new("Ctrl+Enter to save").size(10.0).color(Color32::GRAY));
+            });
+        });
+
+    if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Enter)) {
+        save = true;
+    }
+
+    if save {
+        let before = state.snapshot();
+        match state.apply_draft(&draft) {
+            Ok(()) => {
+                let message = format!("Updated task {}", draft.task_id);
+                state.record_undo(before, &message);
+                state.set_status_with_notices(message);
+                return;
+            }
+            Err(err) => {
+                state.notices.clear();
+                state.set_status(format!("Error: {}", err));
+            }
+        }
+    }
+
+    if open && !cancel {
+        state.editing = Some(draft);
+    }
+}
+
+fn priority_color(priority: Priority) -> Color32 {
+    match priority {
+        Priority::Low => Color32::from_rgb(120, 130, 150),
+        Priority::Medium => Color32::from_rgb(230, 200, 90),
+        Priority::High => Color32::from_rgb(240, 140, 60),
+        Priority::Urgent => Color32::from_rgb(235, 80, 80),
+    }
 }
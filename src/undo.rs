@@ -0,0 +1,55 @@
+use std::collections::VecDeque;
+
+/// Maximum number of undo steps kept in memory
+const MAX_UNDO_STEPS: usize = 100;
+
+/// A serialized copy of the board taken before a change
+#[derive(Debug, Clone)]
+struct Snapshot {
+    label: String,
+    board: String,
+}
+
+/// Bounded undo/redo stack of board snapshots
+///
+/// Snapshots are the board serialized to JSON, so anything that gets
+/// persisted is automatically covered by undo.
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl UndoStack {
+    /// Record a new change. Clears the redo stack.
+    pub fn push(&mut self, label: String, board: String) {
+        self.redo.clear();
+        self.undo.push_back(Snapshot { label, board });
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Take the most recent change, returning its label and the board before it
+    pub fn pop_undo(&mut self) -> Option<(String, String)> {
+        self.undo.pop_back().map(|s| (s.label, s.board))
+    }
+
+    /// Take the most recently undone change, returning its label and the board after it
+    pub fn pop_redo(&mut self) -> Option<(String, String)> {
+        self.redo.pop().map(|s| (s.label, s.board))
+    }
+
+    /// Remember the board as it was before an undo, so it can be redone
+    pub fn push_redo(&mut self, label: String, board: String) {
+        self.redo.push(Snapshot { label, board });
+    }
+
+    /// Put a redone change back on the undo stack without clearing redo
+    pub fn push_undo(&mut self, label: String, board: String) {
+        self.undo.push_back(Snapshot { label, board });
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.pop_front();
+        }
+    }
+}